@@ -0,0 +1,307 @@
+use itertools::Itertools;
+
+use crate::{CartesianGrid, Coords, Edges, ICoords, MOORE};
+
+/// How a generation applies the rule to the grid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Update {
+  /// Every cell is computed from the previous generation.
+  Synchronous,
+  /// Cells are updated in row-major order, later cells seeing earlier updates.
+  InPlace,
+}
+
+/// How a run came to rest.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stability {
+  /// Generation `generation` maps to itself.
+  Fixpoint { generation: usize },
+  /// Generation `start + period` repeats generation `start`.
+  Cycle { start: usize, period: usize },
+}
+
+/// A cell's neighbours as seen by the rule.
+pub struct Neighbourhood<'a, T> {
+  grid: &'a CartesianGrid<T>,
+  coord: Coords,
+  stencil: &'a [ICoords],
+  edges: Edges,
+}
+
+impl<'a, T> Neighbourhood<'a, T> {
+  pub fn coords(&self) -> Coords {
+    self.coord
+  }
+
+  pub fn cells(&self) -> impl Iterator<Item = &'a T> + use<'a, T> {
+    let grid = self.grid;
+    grid
+      .neighbours(self.coord, self.stencil, self.edges)
+      .map(move |c| grid.get(&c))
+  }
+
+  pub fn count(&self, predicate: impl Fn(&T) -> bool) -> usize {
+    self.cells().filter(|c| predicate(c)).count()
+  }
+}
+
+/// Cellular automaton stepping a grid with a rule from a cell and its
+/// neighbourhood to the cell's next state.
+pub struct Automaton<T, R> {
+  grid: CartesianGrid<T>,
+  rules: Rules<R>,
+  changes: Vec<usize>,
+}
+
+/// Everything that decides a generation, kept apart from the grid so that
+/// other grids can be advanced by the same rules.
+struct Rules<R> {
+  rule: R,
+  stencil: Vec<ICoords>,
+  edges: Edges,
+  update: Update,
+}
+
+impl<T, R> Automaton<T, R>
+where
+  T: Clone + PartialEq,
+  R: Fn(&T, &Neighbourhood<T>) -> T,
+{
+  /// Synchronous automaton over the bounded Moore neighbourhood.
+  pub fn new(grid: CartesianGrid<T>, rule: R) -> Self {
+    Automaton {
+      grid,
+      rules: Rules {
+        rule,
+        stencil: MOORE.to_vec(),
+        edges: Edges::Bounded,
+        update: Update::Synchronous,
+      },
+      changes: vec![],
+    }
+  }
+
+  pub fn with_stencil(mut self, stencil: &[ICoords], edges: Edges) -> Self {
+    self.rules.stencil = stencil.to_vec();
+    self.rules.edges = edges;
+    self
+  }
+
+  pub fn with_update(mut self, update: Update) -> Self {
+    self.rules.update = update;
+    self
+  }
+
+  pub fn grid(&self) -> &CartesianGrid<T> {
+    &self.grid
+  }
+
+  /// Generations advanced so far. A step that changes nothing does not count.
+  pub fn generation(&self) -> usize {
+    self.changes.len()
+  }
+
+  /// Number of cells changed by each generation so far.
+  pub fn changes(&self) -> &[usize] {
+    &self.changes
+  }
+
+  /// Applies the rule once to every cell, returning the cells that changed.
+  pub fn step(&mut self) -> Vec<Coords> {
+    let changed = self.rules.advance(&mut self.grid);
+
+    if !changed.is_empty() {
+      self.changes.push(changed.len());
+    }
+    changed
+  }
+
+  /// Steps until a generation maps to itself or repeats an earlier one.
+  ///
+  /// Cycles are found with Brent's algorithm, which compares each generation
+  /// against one saved generation instead of remembering all of them, then
+  /// replays from the starting grid to find where the cycle is entered.
+  pub fn run_until_stable(&mut self) -> Stability {
+    let first = self.generation();
+    let initial = self.grid.clone();
+    let mut saved = self.grid.clone();
+    let (mut power, mut period) = (1, 0);
+
+    loop {
+      if self.step().is_empty() {
+        return Stability::Fixpoint {
+          generation: self.generation(),
+        };
+      }
+      period += 1;
+      if self.grid == saved {
+        break;
+      }
+      if period == power {
+        saved = self.grid.clone();
+        power *= 2;
+        period = 0;
+      }
+    }
+
+    let mut early = initial.clone();
+    let mut late = initial;
+    for _ in 0..period {
+      self.rules.advance(&mut late);
+    }
+    let mut start = first;
+    while early != late {
+      self.rules.advance(&mut early);
+      self.rules.advance(&mut late);
+      start += 1;
+    }
+
+    // Rewind to the first repeat, where the cycle is closed.
+    self.grid = late;
+    self.changes.truncate(start + period);
+    Stability::Cycle { start, period }
+  }
+}
+
+impl<R> Rules<R> {
+  fn advance<T>(&self, grid: &mut CartesianGrid<T>) -> Vec<Coords>
+  where
+    T: Clone + PartialEq,
+    R: Fn(&T, &Neighbourhood<T>) -> T,
+  {
+    match self.update {
+      Update::Synchronous => {
+        let updates = grid
+          .coords()
+          .into_iter()
+          .filter_map(|c| {
+            let next = self.next_state(grid, c);
+            (next != *grid.get(&c)).then_some((c, next))
+          })
+          .collect_vec();
+
+        updates
+          .into_iter()
+          .map(|(c, next)| {
+            grid.set(&c, next);
+            c
+          })
+          .collect_vec()
+      }
+      Update::InPlace => {
+        let mut changed = vec![];
+        for c in grid.coords() {
+          let next = self.next_state(grid, c);
+          if next != *grid.get(&c) {
+            grid.set(&c, next);
+            changed.push(c);
+          }
+        }
+        changed
+      }
+    }
+  }
+
+  fn next_state<T>(&self, grid: &CartesianGrid<T>, coord: Coords) -> T
+  where
+    R: Fn(&T, &Neighbourhood<T>) -> T,
+  {
+    let neighbourhood = Neighbourhood {
+      grid,
+      coord,
+      stencil: &self.stencil,
+      edges: self.edges,
+    };
+    (self.rule)(grid.get(&coord), &neighbourhood)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    CartesianGrid, Edges, ParseError, VON_NEUMANN,
+    automaton::{Automaton, Neighbourhood, Stability, Update},
+  };
+
+  fn grid(text: &str) -> Result<CartesianGrid<char>, ParseError> {
+    CartesianGrid::parse(&text.lines().map(String::from).collect::<Vec<_>>())
+  }
+
+  fn life(cell: &char, neighbours: &Neighbourhood<char>) -> char {
+    match (cell, neighbours.count(|c| *c == '#')) {
+      (_, 3) | ('#', 2) => '#',
+      _ => '.',
+    }
+  }
+
+  #[test]
+  fn blinker_cycles() -> Result<(), ParseError> {
+    let mut automaton = Automaton::new(grid(".....\n..#..\n..#..\n..#..\n.....")?, life);
+
+    assert_eq!(
+      automaton.run_until_stable(),
+      Stability::Cycle {
+        start: 0,
+        period: 2
+      }
+    );
+    assert_eq!(automaton.changes(), [4, 4]);
+    Ok(())
+  }
+
+  #[test]
+  fn cycle_after_a_transient() -> Result<(), ParseError> {
+    let start = grid(".......\n..#....\n..#....\n..#....\n.......\n.......\n......#")?;
+    let mut automaton = Automaton::new(start, life);
+
+    assert_eq!(
+      automaton.run_until_stable(),
+      Stability::Cycle {
+        start: 1,
+        period: 2
+      }
+    );
+    assert_eq!(automaton.changes(), [5, 4, 4]);
+    assert_eq!(automaton.grid().row(2), ['.', '#', '#', '#', '.', '.', '.']);
+    Ok(())
+  }
+
+  #[test]
+  fn block_is_a_fixpoint() -> Result<(), ParseError> {
+    let mut automaton = Automaton::new(grid("....\n.##.\n.##.\n....")?, life);
+
+    assert_eq!(
+      automaton.run_until_stable(),
+      Stability::Fixpoint { generation: 0 }
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn in_place_sees_earlier_updates() -> Result<(), ParseError> {
+    let spread = |cell: &char, neighbours: &Neighbourhood<char>| {
+      if neighbours.count(|c| *c == '#') > 0 {
+        '#'
+      } else {
+        *cell
+      }
+    };
+    let start = grid("#....")?;
+
+    let mut synchronous =
+      Automaton::new(start.clone(), spread).with_stencil(&VON_NEUMANN, Edges::Bounded);
+    synchronous.step();
+    assert_eq!(synchronous.grid().row(0), ['#', '#', '.', '.', '.']);
+
+    let mut in_place = Automaton::new(start, spread)
+      .with_stencil(&VON_NEUMANN, Edges::Bounded)
+      .with_update(Update::InPlace);
+    in_place.step();
+    assert_eq!(in_place.grid().row(0), ['#'; 5]);
+    assert_eq!(
+      in_place.run_until_stable(),
+      Stability::Fixpoint { generation: 1 }
+    );
+    Ok(())
+  }
+}
@@ -0,0 +1,145 @@
+use std::ops::{BitXor, BitXorAssign};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Growable set of small integers, one bit each. Trailing empty words are
+/// trimmed so that equal sets compare and hash equally whatever their history.
+#[derive(Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub struct BitSet {
+  words: Vec<u64>,
+}
+
+impl BitSet {
+  pub fn new() -> Self {
+    BitSet { words: vec![] }
+  }
+
+  pub fn contains(&self, bit: usize) -> bool {
+    self
+      .words
+      .get(bit / WORD_BITS)
+      .is_some_and(|w| w & (1 << (bit % WORD_BITS)) != 0)
+  }
+
+  pub fn insert(&mut self, bit: usize) {
+    let word = bit / WORD_BITS;
+    if word >= self.words.len() {
+      self.words.resize(word + 1, 0);
+    }
+    self.words[word] |= 1 << (bit % WORD_BITS);
+  }
+
+  pub fn remove(&mut self, bit: usize) {
+    if let Some(w) = self.words.get_mut(bit / WORD_BITS) {
+      *w &= !(1 << (bit % WORD_BITS));
+      self.trim();
+    }
+  }
+
+  pub fn toggle(&mut self, bit: usize) {
+    if self.contains(bit) {
+      self.remove(bit);
+    } else {
+      self.insert(bit);
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.words.is_empty()
+  }
+
+  /// Number of bits set.
+  pub fn len(&self) -> usize {
+    self.words.iter().map(|w| w.count_ones() as usize).sum()
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+    self.words.iter().enumerate().flat_map(|(i, &word)| {
+      let mut rest = word;
+      std::iter::from_fn(move || {
+        if rest == 0 {
+          return None;
+        }
+        let bit = rest.trailing_zeros() as usize;
+        rest &= rest - 1;
+        Some(i * WORD_BITS + bit)
+      })
+    })
+  }
+
+  fn trim(&mut self) {
+    while self.words.last() == Some(&0) {
+      self.words.pop();
+    }
+  }
+}
+
+impl FromIterator<usize> for BitSet {
+  fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+    let mut set = BitSet::new();
+    for bit in iter {
+      set.insert(bit);
+    }
+    set
+  }
+}
+
+impl BitXorAssign<&BitSet> for BitSet {
+  fn bitxor_assign(&mut self, rhs: &BitSet) {
+    if rhs.words.len() > self.words.len() {
+      self.words.resize(rhs.words.len(), 0);
+    }
+    for (w, r) in self.words.iter_mut().zip(&rhs.words) {
+      *w ^= r;
+    }
+    self.trim();
+  }
+}
+
+impl BitXor<&BitSet> for &BitSet {
+  type Output = BitSet;
+
+  fn bitxor(self, rhs: &BitSet) -> Self::Output {
+    let mut result = self.clone();
+    result ^= rhs;
+    result
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::bitset::BitSet;
+
+  #[test]
+  fn grows_past_a_word() {
+    let set = BitSet::from_iter([1, 64, 200]);
+
+    assert!(set.contains(64));
+    assert!(set.contains(200));
+    assert!(!set.contains(63));
+    assert!(!set.contains(1000));
+    assert_eq!(set.len(), 3);
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![1, 64, 200]);
+  }
+
+  #[test]
+  fn equal_after_shrinking() {
+    let mut a = BitSet::from_iter([3, 130]);
+    a ^= &BitSet::from_iter([130]);
+
+    assert_eq!(a, BitSet::from_iter([3]));
+    assert_eq!(&a ^ &a, BitSet::new());
+    assert!((&a ^ &a).is_empty());
+  }
+
+  #[test]
+  fn toggle_and_remove() {
+    let mut set = BitSet::new();
+    set.toggle(70);
+    set.toggle(2);
+    set.toggle(70);
+    set.remove(5);
+
+    assert_eq!(set, BitSet::from_iter([2]));
+  }
+}
@@ -1,144 +1,316 @@
-use std::{io::BufRead};
+use std::io::BufRead;
 
-use itertools::Itertools;
+use crate::{
+  Error, ParseError, Result,
+  error::Field,
+  solver::{Answer, Solver},
+};
 
-pub fn what_is_the_password_to_open_the_door(input: &mut dyn BufRead) -> usize {
-  let rotations = parse_input(crate::read_input(input));
-  let mut dial = Dial::new();
+pub fn what_is_the_password_to_open_the_door(input: &mut dyn BufRead) -> Result<usize> {
+  let trace = rotation_trace(input, Dial::default())?;
 
-  rotations
-    .iter()
-    .map(|r| dial.rotate(*r))
-    .filter(|(p, _)| *p == 0)
-    .count()
+  Ok(trace.iter().filter(|r| r.landed_on_target).count())
 }
 
 pub fn what_is_the_password_to_open_the_door_using_password_method(
   input: &mut dyn BufRead,
-) -> u32 {
-  let rotations = parse_input(crate::read_input(input));
+) -> Result<u32> {
+  let rotations = parse_input(&crate::read_input(input)?)?;
+
+  Ok(how_many_times_did_dial_pass_zero(&rotations))
+}
+
+/// Every rotation in the input applied to `dial`, in order.
+pub fn rotation_trace(input: &mut dyn BufRead, mut dial: Dial) -> Result<Vec<Rotation>> {
+  let rotations = parse_input(&crate::read_input(input)?)?;
+
+  Ok(rotations.iter().map(|r| dial.rotate(*r)).collect())
+}
+
+pub struct Day01;
+
+impl Solver for Day01 {
+  fn part1(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(what_is_the_password_to_open_the_door(input)?.into())
+  }
 
-  how_many_times_did_dial_pass_zero(&rotations)
+  fn part2(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(what_is_the_password_to_open_the_door_using_password_method(input)?.into())
+  }
 }
 
-fn how_many_times_did_dial_pass_zero(rotations: &Vec<i32>) -> u32 {
-  let mut dial = Dial::new();
+fn how_many_times_did_dial_pass_zero(rotations: &[i32]) -> u32 {
+  let mut dial = Dial::default();
   rotations
     .iter()
-    .map(|r| dial.rotate(*r))
-    .map(|(_, passed_zero)| passed_zero)
+    .map(|r| dial.rotate(*r).passed_target)
     .sum()
 }
 
-fn parse_input(lines: Vec<String>) -> Vec<i32> {
-  fn parse_rotation(line: &str) -> i32 {
-    let direction = *line.chars().collect_vec().get(0).unwrap();
-    let distance: i32 = line[1..].parse().unwrap();
+fn parse_input(lines: &[String]) -> Result<Vec<i32>, ParseError> {
+  fn parse_rotation(line: Field) -> Result<i32, ParseError> {
+    let sign = match line.text.chars().next() {
+      Some('L') => -1,
+      Some('R') => 1,
+      _ => return Err(line.slice(0, line.text.len().min(1)).error("`L` or `R`")),
+    };
+    // Digits only, as `u32` parsing would also take a leading `+`.
+    let distance = line.slice(1, line.text.len());
+    let clicks = Some(distance.text)
+      .filter(|d| d.bytes().all(|b| b.is_ascii_digit()))
+      .and_then(|d| d.parse::<u32>().ok())
+      .and_then(|d| i32::try_from(d).ok())
+      .ok_or_else(|| distance.error("a distance"))?;
 
-    match direction {
-      'L' => -distance,
-      'R' => distance,
-      _ => panic!("Invalid direction"),
-    }
+    Ok(sign * clicks)
   }
 
-  lines.iter().map(|line| parse_rotation(line)).collect()
+  Field::lines(lines).map(parse_rotation).collect()
 }
 
-struct Dial {
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+  Left,
+  Right,
+}
+
+/// What happened during one rotation of a dial.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Rotation {
+  pub direction: Direction,
+  pub distance: u32,
+  pub from: u32,
+  pub to: u32,
+  /// Clicks that left the dial pointing at the target, the last one included.
+  pub passed_target: u32,
+  pub landed_on_target: bool,
+}
+
+/// Dial numbered `0..size`, pointing at `position`, counting how often it
+/// points at `target`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Dial {
+  size: u32,
   position: u32,
-  max_number: u32,
+  target: u32,
 }
 
 impl Dial {
-  fn new() -> Self {
-    Dial {
-      position: 50,
-      max_number: 99,
+  /// Fails unless `start` and `target` are both on the dial, which leaves no
+  /// dial of size 0.
+  pub fn new(size: u32, start: u32, target: u32) -> Result<Self> {
+    if start >= size || target >= size {
+      return Err(Error::InvalidArgument(format!(
+        "start {} and target {} must be on a dial of {}",
+        start, target, size
+      )));
     }
+    Ok(Dial {
+      size,
+      position: start,
+      target,
+    })
   }
 
-  fn rotate(&mut self, distance: i32) -> (u32, u32) {
-    let before = self.position;
-    let temporary_position = self.position as i32 + distance;
-    self.position = temporary_position.rem_euclid(self.max_number as i32 + 1) as u32;
+  pub fn position(&self) -> u32 {
+    self.position
+  }
+
+  /// Turns the dial right for a positive `distance` and left for a negative
+  /// one, one click at a time.
+  pub fn rotate(&mut self, distance: i32) -> Rotation {
+    let direction = if distance < 0 {
+      Direction::Left
+    } else {
+      Direction::Right
+    };
+    let clicks = distance.unsigned_abs();
+    let from = self.position;
+    let offset = (clicks % self.size) as i64 * distance.signum() as i64;
+    self.position = (from as i64 + offset).rem_euclid(self.size as i64) as u32;
 
-    let passed_zero_times = (temporary_position / (self.max_number as i32 + 1)).abs() as u32 
-      + if temporary_position <= 0 && before != 0 {1} else {0};
+    // The target comes up first after this many clicks, then every `size`.
+    let (size, target, clicks) = (self.size as u64, self.target as u64, clicks as u64);
+    let first = match direction {
+      Direction::Right => (target + size - from as u64) % size,
+      Direction::Left => (from as u64 + size - target) % size,
+    };
+    let first = if first == 0 { size } else { first };
+    let passed_target = if first > clicks {
+      0
+    } else {
+      1 + (clicks - first) / size
+    };
 
-    (self.position, passed_zero_times)
+    Rotation {
+      direction,
+      distance: clicks as u32,
+      from,
+      to: self.position,
+      passed_target: passed_target as u32,
+      landed_on_target: self.position == self.target,
+    }
+  }
+}
+
+impl Default for Dial {
+  /// The safe's dial: 100 positions, starting at 50, counting zeros.
+  fn default() -> Self {
+    Dial {
+      size: 100,
+      position: 50,
+      target: 0,
+    }
   }
 }
 
 #[cfg(test)]
 mod tests {
   use crate::{
+    Error, ParseError, Result,
     day01::{
-      what_is_the_password_to_open_the_door,
+      Dial, Direction, Rotation, rotation_trace, what_is_the_password_to_open_the_door,
       what_is_the_password_to_open_the_door_using_password_method,
     },
     read,
   };
 
   #[test]
-  fn sample_part1_input() {
+  fn sample_part1_input() -> Result<()> {
     assert_eq!(
-      what_is_the_password_to_open_the_door(&mut read("./src/day01/sample.input")),
+      what_is_the_password_to_open_the_door(&mut read("./src/day01/sample.input")?)?,
       3
     );
+    Ok(())
   }
 
   #[test]
-  fn my_part1_input() {
+  fn my_part1_input() -> Result<()> {
     assert_eq!(
-      what_is_the_password_to_open_the_door(&mut read("./src/day01/my.input")),
+      what_is_the_password_to_open_the_door(&mut read("./src/day01/my.input")?)?,
       1036
     );
+    Ok(())
   }
 
   #[test]
-  fn sample_part2_input() {
+  fn sample_part2_input() -> Result<()> {
     assert_eq!(
       what_is_the_password_to_open_the_door_using_password_method(&mut read(
         "./src/day01/sample.input"
-      )),
+      )?)?,
       6
     );
+    Ok(())
+  }
+
+  fn rotate(distance: i32) -> (u32, u32) {
+    let rotation = Dial::default().rotate(distance);
+    (rotation.to, rotation.passed_target)
   }
 
   #[test]
   fn count_passed_zero() {
-    assert_eq!(super::Dial::new().rotate(49), (99, 0));
-    assert_eq!(super::Dial::new().rotate(50), (0, 1));
-    assert_eq!(super::Dial::new().rotate(51), (1, 1));
-    assert_eq!(super::Dial::new().rotate(-49), (1, 0));
-    assert_eq!(super::Dial::new().rotate(-50), (0, 1));
-    assert_eq!(super::Dial::new().rotate(-51), (99, 1));
-    assert_eq!(super::Dial::new().rotate(49 + 100), (99, 1));
-    assert_eq!(super::Dial::new().rotate(50 + 100), (0, 2));
-    assert_eq!(super::Dial::new().rotate(51 + 100), (1, 2));
-    assert_eq!(super::Dial::new().rotate(-49 - 100), (1, 1));
-    assert_eq!(super::Dial::new().rotate(-50 - 100), (0, 2));
-    assert_eq!(super::Dial::new().rotate(-51 - 100), (99, 2));
-    assert_eq!(super::Dial::new().rotate(49 + 100 * 2), (99, 2));
-    assert_eq!(super::Dial::new().rotate(50 + 100 * 2), (0, 3));
-    assert_eq!(super::Dial::new().rotate(-49 - 100 * 2), (1, 2));
-    assert_eq!(super::Dial::new().rotate(-50 - 100 * 2), (0, 3));
+    assert_eq!(rotate(49), (99, 0));
+    assert_eq!(rotate(50), (0, 1));
+    assert_eq!(rotate(51), (1, 1));
+    assert_eq!(rotate(-49), (1, 0));
+    assert_eq!(rotate(-50), (0, 1));
+    assert_eq!(rotate(-51), (99, 1));
+    assert_eq!(rotate(49 + 100), (99, 1));
+    assert_eq!(rotate(50 + 100), (0, 2));
+    assert_eq!(rotate(51 + 100), (1, 2));
+    assert_eq!(rotate(-49 - 100), (1, 1));
+    assert_eq!(rotate(-50 - 100), (0, 2));
+    assert_eq!(rotate(-51 - 100), (99, 2));
+    assert_eq!(rotate(49 + 100 * 2), (99, 2));
+    assert_eq!(rotate(50 + 100 * 2), (0, 3));
+    assert_eq!(rotate(-49 - 100 * 2), (1, 2));
+    assert_eq!(rotate(-50 - 100 * 2), (0, 3));
   }
 
   #[test]
   fn count_passed_zero_multiple_rotations() {
-    assert_eq!(super::how_many_times_did_dial_pass_zero(&vec![-50, 1]), 1);
-    assert_eq!(super::how_many_times_did_dial_pass_zero(&vec![-50, -1]), 1);
+    assert_eq!(super::how_many_times_did_dial_pass_zero(&[-50, 1]), 1);
+    assert_eq!(super::how_many_times_did_dial_pass_zero(&[-50, -1]), 1);
   }
 
   #[test]
-  fn my_part2_input() {
+  fn my_part2_input() -> Result<()> {
     assert_eq!(
       what_is_the_password_to_open_the_door_using_password_method(&mut read(
         "./src/day01/my.input"
-      )),
+      )?)?,
       6228
     );
+    Ok(())
+  }
+
+  #[test]
+  fn invalid_rotation() {
+    assert!(matches!(
+      what_is_the_password_to_open_the_door(&mut "R10\nX5".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(2, 1, "`L` or `R`", "X")
+    ));
+    assert!(matches!(
+      what_is_the_password_to_open_the_door(&mut "L1x".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(1, 2, "a distance", "1x")
+    ));
+    assert!(matches!(
+      what_is_the_password_to_open_the_door(&mut "L-5".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(1, 2, "a distance", "-5")
+    ));
+    assert!(matches!(
+      what_is_the_password_to_open_the_door(&mut "R+5".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(1, 2, "a distance", "+5")
+    ));
+  }
+
+  #[test]
+  fn configurable_dial() -> Result<()> {
+    let trace = rotation_trace(&mut "R400\nL3\nL360\nR0".as_bytes(), Dial::new(360, 0, 37)?)?;
+
+    assert_eq!(
+      trace[0],
+      Rotation {
+        direction: Direction::Right,
+        distance: 400,
+        from: 0,
+        to: 40,
+        passed_target: 2,
+        landed_on_target: false,
+      }
+    );
+    assert_eq!((trace[1].to, trace[1].passed_target), (37, 1));
+    assert!(trace[1].landed_on_target);
+    assert_eq!((trace[2].to, trace[2].passed_target), (37, 1));
+    assert_eq!((trace[3].to, trace[3].passed_target), (37, 0));
+    Ok(())
+  }
+
+  #[test]
+  fn dial_positions_out_of_range() {
+    assert!(matches!(
+      Dial::new(0, 0, 0),
+      Err(Error::InvalidArgument(what)) if what == "start 0 and target 0 must be on a dial of 0"
+    ));
+    assert!(matches!(
+      Dial::new(100, 100, 0),
+      Err(Error::InvalidArgument(_))
+    ));
+    assert!(matches!(
+      Dial::new(100, 0, 100),
+      Err(Error::InvalidArgument(_))
+    ));
+  }
+
+  #[test]
+  fn largest_dial() -> Result<()> {
+    let mut dial = Dial::new(u32::MAX, 0, u32::MAX - 1)?;
+
+    assert_eq!((dial.rotate(5).to, dial.rotate(-7).passed_target), (5, 1));
+    assert_eq!(dial.position(), u32::MAX - 2);
+    assert_eq!(dial.rotate(i32::MIN).passed_target, 0);
+    Ok(())
   }
 }
@@ -1,100 +1,435 @@
 use std::io::BufRead;
 
-use itertools::Itertools;
-
-use crate::read_input;
+use crate::{
+  Error, ParseError, Result,
+  error::{Field, next_field},
+  read_input,
+  solver::{Answer, Solver},
+};
 
 type Long = u64;
 
-pub fn sum_invalid_ids(input: &mut dyn BufRead) -> Long {
-  let ranges = parse_ranges(read_input(input).get(0).unwrap());
-  ranges
-    .iter()
-    .map(|range| sum_invalid_ids_in_range(range, is_id_made_of_two_repeated_parts))
-    .sum()
+pub fn sum_invalid_ids(input: &mut dyn BufRead) -> Result<u128> {
+  let ranges = parse_ranges(&read_input(input)?)?;
+  Ok(ranges.iter().map(sum_doubled_ids_in_range).sum())
+}
+
+pub fn sum_invalid_ids_part2(input: &mut dyn BufRead) -> Result<u128> {
+  let ranges = parse_ranges(&read_input(input)?)?;
+  Ok(ranges.iter().map(sum_repeated_ids_in_range).sum())
+}
+
+/// Every ID in the input ranges that breaks one of `policy`'s rules, in
+/// ascending order within each range, with the first rule it breaks. This
+/// checks IDs one by one, so its cost grows with the width of the ranges.
+pub fn audit(input: &mut dyn BufRead, policy: &Policy) -> Result<Vec<Flagged>> {
+  let ranges = parse_ranges(&read_input(input)?)?;
+  Ok(
+    ranges
+      .iter()
+      .flat_map(|&(start, end)| start..=end)
+      .filter_map(|id| {
+        policy.check(id).map(|rule| Flagged {
+          id,
+          rule: rule.clone(),
+        })
+      })
+      .collect(),
+  )
+}
+
+pub struct Day02;
+
+impl Solver for Day02 {
+  fn part1(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(sum_invalid_ids(input)?.into())
+  }
+
+  fn part2(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(sum_invalid_ids_part2(input)?.into())
+  }
 }
 
-pub fn sum_invalid_ids_part2(input: &mut dyn BufRead) -> Long {
-  let ranges = parse_ranges(read_input(input).get(0).unwrap());
-  ranges
-    .iter()
-    .map(|range| sum_invalid_ids_in_range(range, is_id_made_of_at_least_two_repeated_parts))
+/// Sum of the IDs in `range` made of some block of digits repeated exactly
+/// twice.
+fn sum_doubled_ids_in_range(&(start, end): &(Long, Long)) -> u128 {
+  digit_lengths(start, end)
+    .filter(|digits| digits.is_multiple_of(2))
+    .map(|digits| sum_periodic(start, end, digits, digits / 2))
     .sum()
 }
 
-fn sum_invalid_ids_in_range(range: &(Long, Long), validator: fn(Long) -> bool) -> Long {
-  (range.0..=range.1).filter(|n| validator(*n)).sum()
+/// Sum of the IDs in `range` made of some block of digits repeated at least
+/// twice.
+///
+/// A `digits`-long ID repeats a block whenever it has a period `digits / p`
+/// for some prime `p` dividing `digits`. Having periods `a` and `b` means
+/// having period `gcd(a, b)`, so inclusion–exclusion over sets of those
+/// primes counts every ID exactly once.
+fn sum_repeated_ids_in_range(&(start, end): &(Long, Long)) -> u128 {
+  digit_lengths(start, end)
+    .map(|digits| {
+      let primes = prime_factors(digits);
+      let sum = (1..1usize << primes.len())
+        .map(|subset| {
+          let product: u32 = primes
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| subset & (1 << i) != 0)
+            .map(|(_, p)| p)
+            .product();
+          let sum = sum_periodic(start, end, digits, digits / product) as i128;
+          if subset.count_ones() % 2 == 1 {
+            sum
+          } else {
+            -sum
+          }
+        })
+        .sum::<i128>();
+      sum as u128
+    })
+    .sum()
 }
 
-fn is_id_made_of_two_repeated_parts(id: Long) -> bool {
-  let s = id.to_string();
-  if s.len() % 2 != 0 {
-    false
+/// Sum of the `digits`-digit numbers in `start..=end` with period `block`,
+/// that is `b * (1 + 10^block + 10^2block + ...)` for a `block`-digit `b`.
+fn sum_periodic(start: Long, end: Long, digits: u32, block: u32) -> u128 {
+  let repunit = (10u128.pow(digits) - 1) / (10u128.pow(block) - 1);
+  let first = (start as u128).div_ceil(repunit).max(10u128.pow(block - 1));
+  let last = (end as u128 / repunit).min(10u128.pow(block) - 1);
+
+  if first > last {
+    0
   } else {
-    s[0..s.len() / 2] == s[s.len() / 2..s.len()]
+    repunit * (first + last) * (last - first + 1) / 2
   }
 }
 
-fn is_id_made_of_at_least_two_repeated_parts(id: Long) -> bool {
-  let s = id.to_string();
+fn digit_lengths(start: Long, end: Long) -> impl Iterator<Item = u32> {
+  let digits = |n: Long| n.checked_ilog10().unwrap_or(0) + 1;
+  digits(start)..=digits(end)
+}
 
-  find_divisors(s.len()).iter().any(|&d| {
-    let parts = s.len() / d;
-    let part = &s[0..d];
-    (1..parts).all(|i| &s[i * d..(i + 1) * d] == part)
-  })
+fn prime_factors(mut n: u32) -> Vec<u32> {
+  let mut primes = vec![];
+  let mut p = 2;
+  while n > 1 {
+    if n.is_multiple_of(p) {
+      primes.push(p);
+      while n.is_multiple_of(p) {
+        n /= p;
+      }
+    }
+    p += 1;
+  }
+  primes
 }
 
-fn find_divisors(n: usize) -> Vec<usize> {
-  (1..=n / 2).filter(|i| n % i == 0).rev().collect_vec()
+/// Pattern in the digits of an ID that marks it as invalid.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Rule {
+  /// Some block of digits repeated exactly this many times.
+  RepeatedExactly(usize),
+  /// Some block of digits repeated at least this many times.
+  RepeatedAtLeast(usize),
+  /// Some block of at least this many digits repeated at least twice.
+  MinBlockLength(usize),
+  /// The digits read the same backwards.
+  Palindrome,
+  /// The digits follow a pattern of letters, such as `ABBA`: the same letter
+  /// stands for the same digit and different letters for different digits.
+  DigitPattern(String),
 }
 
-fn parse_ranges(input: &str) -> Vec<(Long, Long)> {
-  input
+impl Rule {
+  fn matches(&self, digits: &[u32]) -> bool {
+    let len = digits.len();
+    let repeated = |times: usize| {
+      times > 1
+        && len.is_multiple_of(times)
+        && digits
+          .chunks(len / times)
+          .all(|block| block == &digits[..len / times])
+    };
+
+    match self {
+      Rule::RepeatedExactly(times) => repeated(*times),
+      Rule::RepeatedAtLeast(times) => (*times..=len).any(repeated),
+      Rule::MinBlockLength(block) => (2..=len / (*block).max(1)).any(repeated),
+      Rule::Palindrome => digits.iter().eq(digits.iter().rev()),
+      Rule::DigitPattern(pattern) => {
+        pattern.chars().count() == len
+          && pattern.chars().zip(digits).all(|(p, d)| {
+            pattern
+              .chars()
+              .zip(digits)
+              .all(|(q, e)| (p == q) == (d == e))
+          })
+      }
+    }
+  }
+}
+
+/// Rules checked against IDs written in `base`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Policy {
+  base: u32,
+  pub rules: Vec<Rule>,
+}
+
+impl Policy {
+  /// Policy without rules. Fails for bases below 2, which have no digits.
+  pub fn new(base: u32) -> Result<Self> {
+    if base < 2 {
+      return Err(Error::InvalidArgument(format!(
+        "base {} has no digits",
+        base
+      )));
+    }
+    Ok(Policy {
+      base,
+      rules: vec![],
+    })
+  }
+
+  pub fn base(&self) -> u32 {
+    self.base
+  }
+
+  pub fn rule(mut self, rule: Rule) -> Self {
+    self.rules.push(rule);
+    self
+  }
+
+  /// First rule `id` breaks, if any.
+  pub fn check(&self, id: Long) -> Option<&Rule> {
+    let digits = self.digits(id);
+    self.rules.iter().find(|rule| rule.matches(&digits))
+  }
+
+  /// Digits of `id` in `base`, most significant first.
+  fn digits(&self, mut id: Long) -> Vec<u32> {
+    let base = Long::from(self.base);
+    let mut digits = vec![];
+    loop {
+      digits.push((id % base) as u32);
+      id /= base;
+      if id == 0 {
+        break;
+      }
+    }
+    digits.reverse();
+    digits
+  }
+}
+
+/// An ID flagged by an audit and the rule that flagged it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Flagged {
+  pub id: Long,
+  pub rule: Rule,
+}
+
+fn parse_ranges(lines: &[String]) -> Result<Vec<(Long, Long)>, ParseError> {
+  let line = Field::lines(lines)
+    .next()
+    .ok_or(ParseError::new(1, 1, "a list of ID ranges", ""))?;
+
+  line
     .split(',')
     .map(|r| {
-      let mut bounds = r.split('-').map(|b| b.parse::<Long>().unwrap());
-      (bounds.next().unwrap(), bounds.next().unwrap())
+      let mut bounds = r.split('-');
+      let start = next_field(&mut bounds, &r, "a range start")?.parse("a range start")?;
+      let end = next_field(&mut bounds, &r, "`-`")?.parse("a range end")?;
+
+      match bounds.next() {
+        Some(extra) => Err(ParseError::new(extra.line, extra.column - 1, "`,`", "-")),
+        None => Ok((start, end)),
+      }
     })
     .collect()
 }
 
 #[cfg(test)]
 mod tests {
+  use proptest::prelude::*;
+
   use crate::{
-    day02::{sum_invalid_ids, sum_invalid_ids_part2},
+    Error, ParseError, Result,
+    day02::{
+      Flagged, Long, Policy, Rule, audit, sum_doubled_ids_in_range, sum_invalid_ids,
+      sum_invalid_ids_part2, sum_repeated_ids_in_range,
+    },
     read,
   };
 
+  fn is_doubled(id: Long) -> bool {
+    let s = id.to_string();
+    s.len().is_multiple_of(2) && s[..s.len() / 2] == s[s.len() / 2..]
+  }
+
+  fn is_repeated(id: Long) -> bool {
+    let s = id.to_string();
+    (1..s.len()).any(|d| s.len().is_multiple_of(d) && s == s[..d].repeat(s.len() / d))
+  }
+
+  fn brute_force(start: Long, end: Long, invalid: fn(Long) -> bool) -> u128 {
+    (start..=end)
+      .filter(|&id| invalid(id))
+      .map(u128::from)
+      .sum()
+  }
+
   #[test]
-  fn sample_part1_input() {
+  fn sample_part1_input() -> Result<()> {
     assert_eq!(
-      sum_invalid_ids(&mut read("./src/day02/sample.input")),
+      sum_invalid_ids(&mut read("./src/day02/sample.input")?)?,
       1227775554
     );
+    Ok(())
   }
 
   #[test]
-  fn my_part1_input() {
+  fn my_part1_input() -> Result<()> {
     assert_eq!(
-      sum_invalid_ids(&mut read("./src/day02/my.input")),
+      sum_invalid_ids(&mut read("./src/day02/my.input")?)?,
       16793817782
     );
+    Ok(())
   }
 
   #[test]
-  fn sample_part2_input() {
+  fn sample_part2_input() -> Result<()> {
     assert_eq!(
-      sum_invalid_ids_part2(&mut read("./src/day02/sample.input")),
+      sum_invalid_ids_part2(&mut read("./src/day02/sample.input")?)?,
       4174379265
     );
+    Ok(())
   }
 
   #[test]
-  fn my_part2_input() {
+  fn my_part2_input() -> Result<()> {
     assert_eq!(
-      sum_invalid_ids_part2(&mut read("./src/day02/my.input")),
+      sum_invalid_ids_part2(&mut read("./src/day02/my.input")?)?,
       27469417404
     );
+    Ok(())
+  }
+
+  #[test]
+  fn invalid_range() {
+    assert!(matches!(
+      sum_invalid_ids(&mut "11-22,95".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(1, 9, "`-`", "")
+    ));
+    assert!(matches!(
+      sum_invalid_ids(&mut "11-22,95-1x5".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(1, 10, "a range end", "1x5")
+    ));
+  }
+
+  #[test]
+  fn whole_digit_lengths() {
+    assert_eq!(sum_doubled_ids_in_range(&(1, 9999)), 495 + 101 * 4905);
+    assert_eq!(
+      sum_repeated_ids_in_range(&(1, 9999)),
+      495 + 111 * 45 + 101 * 4905
+    );
+    assert!(sum_repeated_ids_in_range(&(1, Long::MAX)) > 0);
+    assert_eq!(sum_repeated_ids_in_range(&(Long::MAX - 10, Long::MAX)), 0);
+  }
+
+  #[test]
+  fn audit_lists_flagged_ids() -> Result<()> {
+    let doubled = Policy::new(10)?.rule(Rule::RepeatedExactly(2));
+    let flagged = audit(&mut read("./src/day02/sample.input")?, &doubled)?;
+
+    assert_eq!(
+      flagged.iter().map(|f| f.id).collect::<Vec<_>>(),
+      [11, 22, 99, 1010, 1188511885, 222222, 446446, 38593859]
+    );
+    assert!(flagged.iter().all(|f| f.rule == Rule::RepeatedExactly(2)));
+
+    let repeated = Policy::new(10)?.rule(Rule::RepeatedAtLeast(2));
+    assert_eq!(
+      audit(&mut read("./src/day02/sample.input")?, &repeated)?
+        .iter()
+        .map(|f| u128::from(f.id))
+        .sum::<u128>(),
+      sum_invalid_ids_part2(&mut read("./src/day02/sample.input")?)?
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn policy_rules() -> Result<()> {
+    let policy = Policy::new(10)?
+      .rule(Rule::RepeatedExactly(3))
+      .rule(Rule::MinBlockLength(3))
+      .rule(Rule::Palindrome)
+      .rule(Rule::DigitPattern("ABAC".to_string()));
+
+    assert_eq!(
+      audit(
+        &mut "121212-121213,123123-123123,12321-12321,1213-1214,1215-1215".as_bytes(),
+        &policy
+      )?,
+      [
+        Flagged {
+          id: 121212,
+          rule: Rule::RepeatedExactly(3)
+        },
+        Flagged {
+          id: 123123,
+          rule: Rule::MinBlockLength(3)
+        },
+        Flagged {
+          id: 12321,
+          rule: Rule::Palindrome
+        },
+        Flagged {
+          id: 1213,
+          rule: Rule::DigitPattern("ABAC".to_string())
+        },
+        Flagged {
+          id: 1214,
+          rule: Rule::DigitPattern("ABAC".to_string())
+        },
+        Flagged {
+          id: 1215,
+          rule: Rule::DigitPattern("ABAC".to_string())
+        },
+      ]
+    );
+
+    let binary = Policy::new(2)?.rule(Rule::RepeatedAtLeast(2));
+    assert_eq!(binary.check(0b1010), Some(&Rule::RepeatedAtLeast(2)));
+    assert_eq!(binary.check(0b1011), None);
+    assert_eq!(
+      Policy::new(16)?.rule(Rule::Palindrome).check(0xabba),
+      Some(&Rule::Palindrome)
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn bases_without_digits() {
+    assert!(matches!(
+      Policy::new(0),
+      Err(Error::InvalidArgument(what)) if what == "base 0 has no digits"
+    ));
+    assert!(matches!(
+      Policy::new(1),
+      Err(Error::InvalidArgument(what)) if what == "base 1 has no digits"
+    ));
+  }
+
+  proptest! {
+    #[test]
+    fn closed_form_matches_brute_force(start in 1 as Long..2_000_000, width in 0 as Long..20_000) {
+      let end = start + width;
+      prop_assert_eq!(sum_doubled_ids_in_range(&(start, end)), brute_force(start, end, is_doubled));
+      prop_assert_eq!(sum_repeated_ids_in_range(&(start, end)), brute_force(start, end, is_repeated));
+    }
   }
 }
@@ -1,81 +1,180 @@
 use std::io::BufRead;
 
-use crate::read_input;
+use num_bigint::BigUint;
 
-type Long = u64;
+use crate::{
+  ParseError, Result,
+  error::Field,
+  read_input,
+  solver::{Answer, Solver},
+};
 
-pub fn total_output_joltage(input: &mut dyn BufRead, digits: usize) -> Long {
-  let banks = parse_banks(read_input(input));
-  banks
-    .iter()
-    .map(|bank| find_max_joltage(bank, digits, 0, 0))
-    .sum()
+/// Sum of every bank's maximum joltage, unbounded so that any number of
+/// `digits` fits.
+pub fn total_output_joltage(input: &mut dyn BufRead, digits: usize) -> Result<BigUint> {
+  Ok(
+    max_joltage_selections(input, digits)?
+      .into_iter()
+      .map(|s| s.joltage)
+      .sum(),
+  )
 }
 
-fn parse_banks(lines: Vec<String>) -> Vec<Vec<u32>> {
-  lines
-    .iter()
+/// The batteries turned on in each bank for its maximum joltage.
+pub fn max_joltage_selections(input: &mut dyn BufRead, digits: usize) -> Result<Vec<Selection>> {
+  let banks = parse_banks(&read_input(input)?, digits)?;
+  Ok(
+    banks
+      .iter()
+      .map(|bank| select_batteries(bank, digits))
+      .collect(),
+  )
+}
+
+pub struct Day03;
+
+impl Solver for Day03 {
+  fn part1(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(total_output_joltage(input, 2)?.into())
+  }
+
+  fn part2(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(total_output_joltage(input, 12)?.into())
+  }
+}
+
+/// Batteries turned on in a bank, by position, and the joltage they produce.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Selection {
+  pub positions: Vec<usize>,
+  pub joltage: BigUint,
+}
+
+fn parse_banks(lines: &[String], digits: usize) -> Result<Vec<Vec<u32>>, ParseError> {
+  Field::lines(lines)
     .map(|line| {
-      line
+      let bank = line
         .chars()
-        .map(|c| c.to_digit(10).unwrap() as u32)
-        .collect()
+        .map(|(field, c)| c.to_digit(10).ok_or(field.error("a battery joltage digit")))
+        .collect::<Result<Vec<u32>, ParseError>>()?;
+
+      if bank.len() < digits {
+        Err(line.end().error(&format!("at least {} batteries", digits)))
+      } else {
+        Ok(bank)
+      }
     })
     .collect()
 }
 
-fn find_max_joltage(bank: &Vec<u32>, digits: usize, digit: usize, start: usize) -> Long {
-  let max = bank[start..=bank.len() - digits + digit]
-    .iter()
-    .max()
-    .unwrap();
-  let index = bank
-    .iter()
-    .enumerate()
-    .position(|(i, n)| i >= start && n == max)
-    .unwrap();
-
-  *max as Long * 10u64.pow((digits as i32 - digit as i32 - 1) as u32)
-    + if digit + 1 == digits {
-      0
-    } else {
-      find_max_joltage(bank, digits, digit + 1, index + 1)
+/// Picks `digits` batteries, in order, for the largest joltage. The stack
+/// holds the batteries picked so far; a bigger battery evicts smaller ones
+/// before it while enough batteries remain to fill the rest.
+fn select_batteries(bank: &[u32], digits: usize) -> Selection {
+  let mut skips = bank.len() - digits;
+  let mut picked: Vec<usize> = Vec::with_capacity(bank.len());
+
+  for (i, &joltage) in bank.iter().enumerate() {
+    while skips > 0 && picked.last().is_some_and(|&top| bank[top] < joltage) {
+      picked.pop();
+      skips -= 1;
     }
+    picked.push(i);
+  }
+  picked.truncate(digits);
+
+  Selection {
+    joltage: picked
+      .iter()
+      .fold(BigUint::ZERO, |joltage, &i| joltage * 10u32 + bank[i]),
+    positions: picked,
+  }
 }
 
 #[cfg(test)]
 mod tests {
-  use crate::{day03::total_output_joltage, read};
+  use num_bigint::BigUint;
+
+  use crate::{
+    Error, ParseError, Result,
+    day03::{Selection, max_joltage_selections, total_output_joltage},
+    read,
+  };
 
   #[test]
-  fn sample_part1_input() {
+  fn sample_part1_input() -> Result<()> {
     assert_eq!(
-      total_output_joltage(&mut read("./src/day03/sample.input"), 2),
-      357
+      total_output_joltage(&mut read("./src/day03/sample.input")?, 2)?,
+      BigUint::from(357u64)
     );
+    Ok(())
   }
 
   #[test]
-  fn sample_part1_input_generic() {
+  fn sample_part1_input_generic() -> Result<()> {
     assert_eq!(
-      total_output_joltage(&mut read("./src/day03/sample.input"), 2),
-      357
+      total_output_joltage(&mut read("./src/day03/sample.input")?, 2)?,
+      BigUint::from(357u64)
     );
+    Ok(())
   }
 
   #[test]
-  fn sample_part2_input() {
+  fn sample_part2_input() -> Result<()> {
     assert_eq!(
-      total_output_joltage(&mut read("./src/day03/sample.input"), 12),
-      3121910778619
+      total_output_joltage(&mut read("./src/day03/sample.input")?, 12)?,
+      BigUint::from(3121910778619u64)
     );
+    Ok(())
   }
 
   #[test]
-  fn my_part2_input() {
+  fn my_part2_input() -> Result<()> {
+    assert_eq!(
+      total_output_joltage(&mut read("./src/day03/my.input")?, 12)?,
+      BigUint::from(170520923035051u64)
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn invalid_bank() {
+    assert!(matches!(
+      total_output_joltage(&mut "987\n81a".as_bytes(), 2),
+      Err(Error::Parse(e)) if e == ParseError::new(2, 3, "a battery joltage digit", "a")
+    ));
+    assert!(matches!(
+      total_output_joltage(&mut "987\n8".as_bytes(), 2),
+      Err(Error::Parse(e)) if e == ParseError::new(2, 2, "at least 2 batteries", "")
+    ));
+  }
+
+  #[test]
+  fn selected_positions() -> Result<()> {
+    let selections =
+      max_joltage_selections(&mut "818181911112111\n987654321111111".as_bytes(), 12)?;
+
+    assert_eq!(
+      selections[0],
+      Selection {
+        positions: vec![0, 2, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14],
+        joltage: BigUint::from(888911112111u64),
+      }
+    );
+    assert_eq!(selections[1].joltage, BigUint::from(987654321111u64));
+    Ok(())
+  }
+
+  #[test]
+  fn more_than_nineteen_digits() -> Result<()> {
+    let bank = "1234567890".repeat(4);
+
     assert_eq!(
-      total_output_joltage(&mut read("./src/day03/my.input"), 12),
-      170520923035051
+      total_output_joltage(&mut format!("{}\n{}", bank, bank).as_bytes(), 30)?,
+      "1846913578024691357802469135780"
+        .parse::<BigUint>()
+        .unwrap()
     );
+    Ok(())
   }
 }
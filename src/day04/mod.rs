@@ -1,124 +1,187 @@
-use std::{io::BufRead, ops::Add};
+use std::io::BufRead;
 
 use itertools::Itertools;
 
-use crate::{CartesianGrid, Coords, ICoords, read_input};
+use crate::{
+  CartesianGrid, FromCell, Result,
+  automaton::{Automaton, Neighbourhood},
+  read_input,
+  solver::{Answer, Solver},
+};
 
-pub fn accessible_paper_rolls(input: &mut dyn BufRead) -> usize {
-  let lines = read_input(input);
-  let printing_department = CartesianGrid::from(lines);
+pub fn accessible_paper_rolls(input: &mut dyn BufRead) -> Result<usize> {
+  let printing_department = parse_printing_department(input)?;
 
-  printing_department.get_accessible_paper_rolls().len()
+  Ok(Automaton::new(printing_department, removal).step().len())
 }
 
-pub fn how_many_paper_rolls_can_be_removed(input: &mut dyn BufRead) -> usize {
-  let lines = read_input(input);
-  let mut printing_department = CartesianGrid::from(lines);
+pub fn how_many_paper_rolls_can_be_removed(input: &mut dyn BufRead) -> Result<usize> {
+  Ok(paper_rolls_removed_per_round(input)?.iter().sum())
+}
 
-  let mut total_removed_paper_rolls = 0;
+/// Number of paper rolls removed in each round, every round removing all the
+/// rolls accessible at its start.
+pub fn paper_rolls_removed_per_round(input: &mut dyn BufRead) -> Result<Vec<usize>> {
+  let mut printing_department = parse_printing_department(input)?;
+  let mut adjacent_rolls = printing_department.map(|c, _| {
+    printing_department
+      .moore_neighbours(c)
+      .filter(|n| *printing_department.get(n) == Cell::PaperRoll)
+      .count()
+  });
+
+  let mut accessible = printing_department
+    .coords()
+    .into_iter()
+    .filter(|c| *printing_department.get(c) == Cell::PaperRoll && *adjacent_rolls.get(c) < 4)
+    .collect_vec();
+  let mut rounds = vec![];
+
+  while !accessible.is_empty() {
+    for roll in &accessible {
+      printing_department.set(roll, Cell::Removed);
+    }
+    rounds.push(accessible.len());
+
+    // Counts only go down, so a roll becomes accessible exactly when its
+    // count drops from four to three.
+    let mut next = vec![];
+    for roll in &accessible {
+      for n in printing_department.moore_neighbours(*roll) {
+        if *printing_department.get(&n) == Cell::PaperRoll {
+          let count = *adjacent_rolls.get(&n) - 1;
+          adjacent_rolls.set(&n, count);
+          if count == 3 {
+            next.push(n);
+          }
+        }
+      }
+    }
+    accessible = next;
+  }
 
-  loop {
-    let paper_rolls = printing_department.get_accessible_paper_rolls();
+  Ok(rounds)
+}
 
-    if paper_rolls.len() == 0 {
-      break;
-    }
+pub struct Day04;
 
-    printing_department.remove_paper_rolls(&paper_rolls);
-    total_removed_paper_rolls += paper_rolls.len();
+impl Solver for Day04 {
+  fn part1(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(accessible_paper_rolls(input)?.into())
   }
 
-  total_removed_paper_rolls
+  fn part2(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(how_many_paper_rolls_can_be_removed(input)?.into())
+  }
 }
 
-trait PrintingDepartment {
-  fn get_adjacent_paper_rolls(&self, coord: &Coords) -> Vec<Coords>;
-  fn get_accessible_paper_rolls(&self) -> Vec<Coords>;
-  fn remove_paper_rolls(&mut self, paper_rolls: &Vec<Coords>);
+fn parse_printing_department(input: &mut dyn BufRead) -> Result<CartesianGrid<Cell>> {
+  let lines = read_input(input)?;
+  Ok(CartesianGrid::parse(&lines)?)
 }
 
-impl PrintingDepartment for CartesianGrid<char> {
-  fn get_accessible_paper_rolls(&self) -> Vec<Coords> {
-    self
-      .coords()
-      .iter()
-      .filter(|c| *self.get(c) == '@')
-      .filter(|c| self.get_adjacent_paper_rolls(c).len() < 4)
-      .map(|c| *c)
-      .collect_vec()
-  }
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Cell {
+  Empty,
+  PaperRoll,
+  Removed,
+}
 
-  fn remove_paper_rolls(&mut self, paper_rolls: &Vec<Coords>) {
-    for pr in paper_rolls {
-      self.set(pr, 'x');
-    }
-  }
+impl FromCell for Cell {
+  const EXPECTED: &'static str = "`.`, `@` or `x`";
 
-  fn get_adjacent_paper_rolls(&self, coords: &Coords) -> Vec<Coords> {
-    self
-      .get_adjacent_coords_in_bounds(*coords)
-      .iter()
-      .filter(|c| *self.get(c) == '@')
-      .map(|c| *c)
-      .collect_vec()
+  fn from_cell(c: char) -> Option<Self> {
+    match c {
+      '.' => Some(Cell::Empty),
+      '@' => Some(Cell::PaperRoll),
+      'x' => Some(Cell::Removed),
+      _ => None,
+    }
   }
 }
 
-impl CartesianGrid<char> {
-  fn get_adjacent_coords_in_bounds(&self, coords: Coords) -> Vec<Coords> {
-    vec![
-      coords.add(ICoords(-1, 1)),
-      coords.add_y(1),
-      coords.add(ICoords(1, 1)),
-      coords.add_x(1),
-      coords.add(ICoords(-1, -1)),
-      coords.sub_y(1),
-      coords.add(ICoords(1, -1)),
-      coords.sub_x(1),
-    ]
-    .iter()
-    .filter(|c| self.in_grid(c))
-    .map(|c| c.to_coords().unwrap())
-    .collect_vec()
+/// A paper roll can be removed when fewer than four of its neighbours are
+/// paper rolls.
+fn removal(cell: &Cell, neighbours: &Neighbourhood<Cell>) -> Cell {
+  if *cell == Cell::PaperRoll && neighbours.count(|c| *c == Cell::PaperRoll) < 4 {
+    Cell::Removed
+  } else {
+    *cell
   }
 }
 
 #[cfg(test)]
 mod tests {
   use crate::{
-    day04::{accessible_paper_rolls, how_many_paper_rolls_can_be_removed},
+    Error, ParseError, Result,
+    automaton::Automaton,
+    day04::{
+      accessible_paper_rolls, how_many_paper_rolls_can_be_removed, paper_rolls_removed_per_round,
+      parse_printing_department, removal,
+    },
     read,
   };
 
   #[test]
-  fn sample_part1_input() {
+  fn sample_part1_input() -> Result<()> {
     assert_eq!(
-      accessible_paper_rolls(&mut read("./src/day04/sample.input")),
+      accessible_paper_rolls(&mut read("./src/day04/sample.input")?)?,
       13
     );
+    Ok(())
   }
 
   #[test]
-  fn my_part1_input() {
+  fn my_part1_input() -> Result<()> {
     assert_eq!(
-      accessible_paper_rolls(&mut read("./src/day04/my.input")),
+      accessible_paper_rolls(&mut read("./src/day04/my.input")?)?,
       1435
     );
+    Ok(())
   }
 
   #[test]
-  fn sample_part2_input() {
+  fn sample_part2_input() -> Result<()> {
     assert_eq!(
-      how_many_paper_rolls_can_be_removed(&mut read("./src/day04/sample.input")),
+      how_many_paper_rolls_can_be_removed(&mut read("./src/day04/sample.input")?)?,
       43
     );
+    Ok(())
   }
 
   #[test]
-  fn my_part2_input() {
+  fn my_part2_input() -> Result<()> {
     assert_eq!(
-      how_many_paper_rolls_can_be_removed(&mut read("./src/day04/my.input")),
+      how_many_paper_rolls_can_be_removed(&mut read("./src/day04/my.input")?)?,
       8623
     );
+    Ok(())
+  }
+
+  #[test]
+  fn unknown_cell() {
+    assert!(matches!(
+      accessible_paper_rolls(&mut "..@\n.#@".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(2, 2, "`.`, `@` or `x`", "#")
+    ));
+  }
+
+  #[test]
+  fn rounds_match_the_automaton() -> Result<()> {
+    let mut removals = Automaton::new(
+      parse_printing_department(&mut read("./src/day04/my.input")?)?,
+      removal,
+    );
+    removals.run_until_stable();
+
+    assert_eq!(
+      paper_rolls_removed_per_round(&mut read("./src/day04/my.input")?)?,
+      removals.changes()
+    );
+    assert_eq!(
+      paper_rolls_removed_per_round(&mut read("./src/day04/sample.input")?)?,
+      [13, 12, 7, 5, 2, 1, 1, 1, 1]
+    );
+    Ok(())
   }
 }
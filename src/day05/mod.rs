@@ -1,144 +1,119 @@
-use std::{cmp, io::BufRead};
+use std::{io::BufRead, ops::RangeInclusive};
 
-use itertools::Itertools;
-
-use crate::read_input;
+use crate::{
+  ParseError, Result,
+  error::Field,
+  interval_set::IntervalSet,
+  read_input,
+  solver::{Answer, Solver},
+};
 
 type Long = u64;
 
-pub fn how_many_ids_are_fresh(input: &mut dyn BufRead) -> usize {
-  let lines = read_input(input);
-  let (ranges, ids) = parse_lines(lines);
+pub fn how_many_ids_are_fresh(input: &mut dyn BufRead) -> Result<usize> {
+  let lines = read_input(input)?;
+  let (fresh, ids) = parse_lines(&lines)?;
 
-  ids
-    .iter()
-    .filter(|id| ranges.iter().any(|r| r.in_range(id)))
-    .count()
+  Ok(ids.iter().filter(|&&id| fresh.contains(id)).count())
 }
 
-pub fn how_many_ids_are_fresh_according_to_fresh_ranges(input: &mut dyn BufRead) -> Long {
-  let lines = read_input(input);
-  let (ranges, _) = parse_lines(lines);
+pub fn how_many_ids_are_fresh_according_to_fresh_ranges(input: &mut dyn BufRead) -> Result<u128> {
+  let lines = read_input(input)?;
+  let (fresh, _) = parse_lines(&lines)?;
 
-  join_overlapping_ranges(&ranges)
-    .iter()
-    .map(|r| r.end - r.start + 1)
-    .sum()
+  Ok(fresh.len())
 }
 
-fn parse_lines(lines: Vec<String>) -> (Vec<Range>, Vec<Long>) {
-  fn parse_range(line: &String) -> Range {
-    Range {
-      start: line.split_once('-').unwrap().0.parse::<Long>().unwrap(),
-      end: line.split_once('-').unwrap().1.parse::<Long>().unwrap(),
-    }
-  }
+pub struct Day05;
 
-  fn parse_id(line: &String) -> Long {
-    line.parse::<Long>().unwrap()
+impl Solver for Day05 {
+  fn part1(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(how_many_ids_are_fresh(input)?.into())
   }
 
-  let mut parts = lines.split(|line| line == "");
-  let ranges = parts
-    .next()
-    .unwrap()
-    .iter()
-    .map(|line| parse_range(line))
-    .collect();
-  let ids = parts
-    .next()
-    .unwrap()
-    .iter()
-    .map(|line| parse_id(line))
-    .collect_vec();
-
-  (ranges, ids)
-}
-
-#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
-struct Range {
-  start: Long,
-  end: Long,
-}
-
-impl Range {
-  fn in_range(&self, id: &Long) -> bool {
-    *id >= self.start && *id <= self.end
+  fn part2(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(how_many_ids_are_fresh_according_to_fresh_ranges(input)?.into())
   }
 }
 
-fn join_overlapping_ranges(ranges: &Vec<Range>) -> Vec<Range> {
-  let mut to_join: Vec<Range> = ranges.iter().map(|&r| r.clone()).collect_vec();
-
-  loop {
-    let joined = join(&to_join);
+fn parse_lines(lines: &[String]) -> Result<(IntervalSet<Long>, Vec<Long>), ParseError> {
+  fn parse_range(line: Field) -> Result<RangeInclusive<Long>, ParseError> {
+    let (start, end) = line.split_once('-')?;
+    let range = start.parse("a range start")?..=end.parse("a range end")?;
 
-    if joined.len() == to_join.len() {
-      return joined;
+    if range.is_empty() {
+      Err(end.error(&format!("a range end not below {}", range.start())))
     } else {
-      to_join = joined;
-    }
-  }
-}
-
-fn join(ranges: &Vec<Range>) -> Vec<Range> {
-  let mut joined: Vec<Range> = Vec::new();
-
-  for r in ranges {
-    if let Some(to_join) = joined
-      .clone()
-      .iter()
-      .find_position(|j| !(j.end + 2 <= r.start || j.start >= r.end + 2))
-    {
-      joined.remove(to_join.0);
-      joined.push(Range {
-        start: cmp::min(r.start, to_join.1.start),
-        end: cmp::max(r.end, to_join.1.end),
-      });
-    } else {
-      joined.push(*r);
+      Ok(range)
     }
   }
 
-  joined
+  let mut lines = Field::lines(lines);
+  let fresh = lines
+    .by_ref()
+    .take_while(|line| !line.text.is_empty())
+    .map(parse_range)
+    .collect::<Result<IntervalSet<Long>, ParseError>>()?;
+  let ids = lines
+    .map(|line| line.parse("an ingredient ID"))
+    .collect::<Result<Vec<Long>, ParseError>>()?;
+
+  Ok((fresh, ids))
 }
 
 #[cfg(test)]
 mod tests {
   use crate::{
+    Error, ParseError, Result,
     day05::{how_many_ids_are_fresh, how_many_ids_are_fresh_according_to_fresh_ranges},
     read,
   };
 
   #[test]
-  fn sample_part1_input() {
+  fn sample_part1_input() -> Result<()> {
     assert_eq!(
-      how_many_ids_are_fresh(&mut read("./src/day05/sample.input")),
+      how_many_ids_are_fresh(&mut read("./src/day05/sample.input")?)?,
       3
     );
+    Ok(())
   }
 
   #[test]
-  fn my_part1_input() {
+  fn my_part1_input() -> Result<()> {
     assert_eq!(
-      how_many_ids_are_fresh(&mut read("./src/day05/my.input")),
+      how_many_ids_are_fresh(&mut read("./src/day05/my.input")?)?,
       789
     );
+    Ok(())
   }
 
   #[test]
-  fn sample_part2_input() {
+  fn sample_part2_input() -> Result<()> {
     assert_eq!(
-      how_many_ids_are_fresh_according_to_fresh_ranges(&mut read("./src/day05/sample.input")),
+      how_many_ids_are_fresh_according_to_fresh_ranges(&mut read("./src/day05/sample.input")?)?,
       14
     );
+    Ok(())
   }
 
   #[test]
-  fn my_part2_input() {
+  fn my_part2_input() -> Result<()> {
     assert_eq!(
-      how_many_ids_are_fresh_according_to_fresh_ranges(&mut read("./src/day05/my.input")),
+      how_many_ids_are_fresh_according_to_fresh_ranges(&mut read("./src/day05/my.input")?)?,
       343329651880509
     );
+    Ok(())
+  }
+
+  #[test]
+  fn invalid_database() {
+    assert!(matches!(
+      how_many_ids_are_fresh(&mut "3-5\n10:14\n\n1".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(2, 6, "`-`", "")
+    ));
+    assert!(matches!(
+      how_many_ids_are_fresh(&mut "3-5\n\n1\nfive".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(4, 1, "an ingredient ID", "five")
+    ));
   }
 }
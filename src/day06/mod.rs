@@ -1,125 +1,479 @@
-use std::io::BufRead;
+use std::{
+  fmt::{self, Display},
+  io::BufRead,
+  ops::Range,
+};
 
 use itertools::Itertools;
+use num_bigint::BigInt;
 
-use crate::read_input;
+use crate::{
+  Error, ParseError, Result,
+  error::Field,
+  read_input,
+  solver::{Answer, Solver},
+};
 
 type Long = u64;
 
-pub fn answers_sum(input: &mut dyn BufRead) -> Long {
-  let lines = read_input(input);
-  let (numbers, operations) = (parse_numbers(&lines), parse_operations(&lines));
-
-  let problems = operations.len();
-
-  (0..problems)
-    .map(|i| {
-      solve_problem(
-        &numbers.iter().map(|line| line[i]).collect_vec(),
-        operations[i],
-      )
-    })
-    .sum()
+/// Sum of the answers read left to right, failing if any step overflows.
+pub fn answers_sum(input: &mut dyn BufRead) -> Result<Long> {
+  checked_answers_sum(&read_input(input)?, Reading::LeftToRight)
 }
 
-pub fn rtl_answers_sum(input: &mut dyn BufRead) -> Long {
-  let lines = read_input(input);
-  let (numbers, operations) = (parse_numbers_rtl(&lines), parse_operations(&lines));
+/// Sum of the answers read right to left, failing if any step overflows.
+pub fn rtl_answers_sum(input: &mut dyn BufRead) -> Result<Long> {
+  checked_answers_sum(&read_input(input)?, Reading::RightToLeft)
+}
 
-  let problems = operations.len();
+/// Sum of the answers in arbitrary precision, for worksheets that overflow
+/// 64 bits.
+pub fn big_answers_sum(input: &mut dyn BufRead, reading: Reading) -> Result<BigInt> {
+  let lines = read_input(input)?;
+  let worksheet = Worksheet::parse(&lines)?;
 
-  (0..problems)
-    .map(|i| solve_problem(&numbers[i], operations[i]))
+  worksheet
+    .problems
+    .iter()
+    .map(|p| {
+      let numbers = p.numbers(reading)?;
+      p.operator
+        .evaluate(numbers.into_iter().map(BigInt::from), Operator::apply_big)
+    })
     .sum()
 }
 
-fn parse_operations(lines: &Vec<String>) -> Vec<char> {
-  lines[lines.len() - 1]
-    .split_ascii_whitespace()
-    .map(|o| o.chars().collect_vec()[0])
-    .collect_vec()
+/// Every problem solved, in worksheet order, failing if any step overflows.
+pub fn equations(input: &mut dyn BufRead, reading: Reading) -> Result<Vec<Equation>> {
+  solve(&read_input(input)?, reading)
 }
 
-fn parse_numbers(lines: &Vec<String>) -> Vec<Vec<Long>> {
-  lines[0..lines.len() - 1]
+fn checked_answers_sum(lines: &[String], reading: Reading) -> Result<Long> {
+  solve(lines, reading)?
     .iter()
-    .map(|line| {
-      line
-        .split_ascii_whitespace()
-        .map(|n| n.parse::<Long>().unwrap())
-        .collect_vec()
+    .try_fold(0 as Long, |total, equation| {
+      total
+        .checked_add(equation.result)
+        .ok_or_else(|| Error::Arithmetic(format!("{} + {} overflows", total, equation.result)))
     })
-    .collect_vec()
 }
 
-fn parse_numbers_rtl(lines: &Vec<String>) -> Vec<Vec<Long>> {
-  let to_transpose = lines[0..lines.len() - 1]
+fn solve(lines: &[String], reading: Reading) -> Result<Vec<Equation>> {
+  let worksheet = Worksheet::parse(lines)?;
+
+  worksheet
+    .problems
     .iter()
-    .map(|s| s.chars().collect_vec())
-    .collect_vec();
-  let transposed_line_len = to_transpose.len();
-  let line_len = lines[0].len();
-
-  let transposed = (0..line_len)
-    .map(|i| {
-      (0..transposed_line_len)
-        .map(|t| to_transpose[t][i])
-        .collect::<String>()
+    .enumerate()
+    .map(|(index, p)| {
+      let operands = p.numbers(reading)?;
+      let result = p
+        .operator
+        .evaluate(operands.iter().copied(), Operator::apply)?;
+
+      Ok(Equation {
+        index,
+        columns: p.columns.clone(),
+        operands,
+        operator: p.operator,
+        result,
+      })
     })
-    .map(|s| s)
-    .collect_vec();
+    .collect()
+}
 
-  transposed
-    .split(|t| t.trim() == "")
-    .map(|p| {
-      p.iter()
-        .map(|n| n.trim().parse::<Long>().unwrap())
-        .collect_vec()
+pub struct Day06;
+
+impl Solver for Day06 {
+  fn part1(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(answers_sum(input)?.into())
+  }
+
+  fn part2(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(rtl_answers_sum(input)?.into())
+  }
+}
+
+/// A solved problem, kept so that a total can be traced back to the columns
+/// it came from.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Equation {
+  /// Position of the problem from the left, counted from 0.
+  pub index: usize,
+  /// Character columns the problem occupies, counted from 0.
+  pub columns: Range<usize>,
+  pub operands: Vec<Long>,
+  pub operator: Operator,
+  pub result: Long,
+}
+
+impl Display for Equation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let separator = format!(" {} ", self.operator.symbol());
+    write!(
+      f,
+      "{} = {}",
+      self.operands.iter().join(&separator),
+      self.result
+    )
+  }
+}
+
+/// Which way the numbers of a problem are written.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Reading {
+  /// One number per row.
+  LeftToRight,
+  /// One number per column, most significant digit at the top, rightmost
+  /// column first.
+  RightToLeft,
+}
+
+/// Operation folded over a problem's numbers from the first one on.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Operator {
+  Add,
+  Multiply,
+  Subtract,
+  Divide,
+  Min,
+  Max,
+  Remainder,
+}
+
+impl Operator {
+  const ALL: [Operator; 7] = [
+    Operator::Add,
+    Operator::Multiply,
+    Operator::Subtract,
+    Operator::Divide,
+    Operator::Min,
+    Operator::Max,
+    Operator::Remainder,
+  ];
+
+  pub fn symbol(&self) -> &'static str {
+    match self {
+      Operator::Add => "+",
+      Operator::Multiply => "*",
+      Operator::Subtract => "-",
+      Operator::Divide => "/",
+      Operator::Min => "min",
+      Operator::Max => "max",
+      Operator::Remainder => "%",
+    }
+  }
+
+  fn parse(field: Field) -> Result<Operator, ParseError> {
+    Operator::ALL
+      .into_iter()
+      .find(|o| o.symbol() == field.text)
+      .ok_or_else(|| field.error("`+`, `*`, `-`, `/`, `min`, `max` or `%`"))
+  }
+
+  /// Folds `numbers` with `apply`, which returns None when a step overflows or
+  /// divides by zero.
+  fn evaluate<N: Display>(
+    self,
+    numbers: impl IntoIterator<Item = N>,
+    apply: impl Fn(Operator, &N, &N) -> Option<N>,
+  ) -> Result<N> {
+    let mut numbers = numbers.into_iter();
+    let first = numbers
+      .next()
+      .ok_or_else(|| Error::Arithmetic(format!("`{}` without numbers", self.symbol())))?;
+
+    numbers.try_fold(first, |a, b| {
+      apply(self, &a, &b).ok_or_else(|| {
+        Error::Arithmetic(format!(
+          "{} {} {} overflows or divides by zero",
+          a,
+          self.symbol(),
+          b
+        ))
+      })
     })
-    .collect_vec()
+  }
+
+  fn apply(self, a: &Long, b: &Long) -> Option<Long> {
+    match self {
+      Operator::Add => a.checked_add(*b),
+      Operator::Multiply => a.checked_mul(*b),
+      Operator::Subtract => a.checked_sub(*b),
+      Operator::Divide => a.checked_div(*b),
+      Operator::Min => Some(*a.min(b)),
+      Operator::Max => Some(*a.max(b)),
+      Operator::Remainder => a.checked_rem(*b),
+    }
+  }
+
+  fn apply_big(self, a: &BigInt, b: &BigInt) -> Option<BigInt> {
+    match self {
+      Operator::Add => Some(a + b),
+      Operator::Multiply => Some(a * b),
+      Operator::Subtract => Some(a - b),
+      Operator::Divide => (*b != BigInt::ZERO).then(|| a / b),
+      Operator::Min => Some(a.min(b).clone()),
+      Operator::Max => Some(a.max(b).clone()),
+      Operator::Remainder => (*b != BigInt::ZERO).then(|| a % b),
+    }
+  }
 }
 
-fn solve_problem(numbers: &Vec<Long>, operation: char) -> Long {
-  if operation == '+' {
-    numbers.iter().sum()
-  } else {
-    numbers.iter().fold(1, |a, b| a * b)
+/// Rows of numbers above a row of operators, with problems separated by
+/// columns that are blank on every row.
+struct Worksheet<'a> {
+  problems: Vec<Problem<'a>>,
+}
+
+/// One problem's block of columns. Each cell is its number row's text within
+/// `columns`, cut short where the row ends.
+struct Problem<'a> {
+  columns: Range<usize>,
+  cells: Vec<Field<'a>>,
+  operator: Operator,
+}
+
+impl<'a> Worksheet<'a> {
+  fn parse(lines: &'a [String]) -> Result<Self, ParseError> {
+    let (operators, rows) =
+      lines
+        .split_last()
+        .ok_or(ParseError::new(1, 1, "a line of operators", ""))?;
+
+    // Columns are counted in bytes, which only line up for ASCII.
+    for line in Field::lines(lines) {
+      if let Some((field, _)) = line.chars().find(|(_, c)| !c.is_ascii()) {
+        return Err(field.error("an ASCII character"));
+      }
+    }
+
+    let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+    let blank = |x: &usize| {
+      lines.iter().all(|line| {
+        line
+          .as_bytes()
+          .get(*x)
+          .is_none_or(|b| b.is_ascii_whitespace())
+      })
+    };
+    let cell = |line: Field<'a>, columns: &Range<usize>| {
+      let len = line.text.len();
+      line.slice(columns.start.min(len), columns.end.min(len))
+    };
+    let operators = Field::line(rows.len(), operators);
+
+    let problems = (0..width)
+      .collect_vec()
+      .split(blank)
+      .filter(|span| !span.is_empty())
+      .map(|span| {
+        let columns = span[0]..span[span.len() - 1] + 1;
+        let operator = Operator::parse(cell(operators, &columns).trim())?;
+
+        Ok(Problem {
+          cells: Field::lines(rows).map(|row| cell(row, &columns)).collect(),
+          columns,
+          operator,
+        })
+      })
+      .collect::<Result<_, ParseError>>()?;
+
+    Ok(Worksheet { problems })
+  }
+}
+
+impl Problem<'_> {
+  fn numbers(&self, reading: Reading) -> Result<Vec<Long>, ParseError> {
+    match reading {
+      Reading::LeftToRight => self.ltr_numbers(),
+      Reading::RightToLeft => self.rtl_numbers(),
+    }
+  }
+
+  /// Numbers written across the rows, top to bottom.
+  fn ltr_numbers(&self) -> Result<Vec<Long>, ParseError> {
+    self
+      .cells
+      .iter()
+      .map(|cell| cell.trim().parse("a number"))
+      .collect()
+  }
+
+  /// Numbers written down the columns, most significant digit at the top,
+  /// rightmost column first.
+  fn rtl_numbers(&self) -> Result<Vec<Long>, ParseError> {
+    self
+      .columns
+      .clone()
+      .rev()
+      .map(|x| {
+        let digits = self
+          .cells
+          .iter()
+          .map(|cell| {
+            cell
+              .text
+              .as_bytes()
+              .get(x - self.columns.start)
+              .map_or(' ', |&b| b as char)
+          })
+          .collect::<String>();
+        let top = digits.len() - digits.trim_start().len();
+
+        digits
+          .trim()
+          .parse()
+          .map_err(|_| ParseError::new(top + 1, x + 1, "a vertical number", digits.trim()))
+      })
+      .collect()
   }
 }
 
 #[cfg(test)]
 mod tests {
+  use num_bigint::BigInt;
+
   use crate::{
-    day06::{answers_sum, rtl_answers_sum},
+    Error, ParseError, Result,
+    day06::{
+      Equation, Operator, Reading, answers_sum, big_answers_sum, equations, rtl_answers_sum,
+    },
     read,
   };
 
   #[test]
-  fn sample_part1_input() {
-    assert_eq!(answers_sum(&mut read("./src/day06/sample.input")), 4277556);
+  fn sample_part1_input() -> Result<()> {
+    assert_eq!(
+      answers_sum(&mut read("./src/day06/sample.input")?)?,
+      4277556
+    );
+    Ok(())
   }
 
   #[test]
-  fn my_part1_input() {
+  fn my_part1_input() -> Result<()> {
     assert_eq!(
-      answers_sum(&mut read("./src/day06/my.input")),
+      answers_sum(&mut read("./src/day06/my.input")?)?,
       4771265398012
     );
+    Ok(())
   }
 
   #[test]
-  fn sample_part2_input() {
+  fn sample_part2_input() -> Result<()> {
     assert_eq!(
-      rtl_answers_sum(&mut read("./src/day06/sample.input")),
+      rtl_answers_sum(&mut read("./src/day06/sample.input")?)?,
       3263827
     );
+    Ok(())
   }
 
   #[test]
-  fn my_part2_input() {
+  fn my_part2_input() -> Result<()> {
     assert_eq!(
-      rtl_answers_sum(&mut read("./src/day06/my.input")),
+      rtl_answers_sum(&mut read("./src/day06/my.input")?)?,
       10695785245101
     );
+    Ok(())
+  }
+
+  #[test]
+  fn invalid_worksheet() {
+    assert!(matches!(
+      answers_sum(&mut "1 2\n3 4\n+ ^".as_bytes()),
+      Err(Error::Parse(e))
+        if e == ParseError::new(3, 3, "`+`, `*`, `-`, `/`, `min`, `max` or `%`", "^")
+    ));
+    assert!(matches!(
+      answers_sum(&mut "1 2\n3\n+ *".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(2, 2, "a number", "")
+    ));
+    assert!(matches!(
+      rtl_answers_sum(&mut "12 3\n4x 5\n+  *".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(1, 2, "a vertical number", "2x")
+    ));
+  }
+
+  #[test]
+  fn ragged_rows() -> Result<()> {
+    let worksheet = "123 4\n 45 56\n  6 7\n*   +";
+
+    assert_eq!(answers_sum(&mut worksheet.as_bytes())?, 33210 + 67);
+    assert_eq!(
+      rtl_answers_sum(&mut worksheet.as_bytes())?,
+      356 * 24 + 6 + 457
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn operators() -> Result<()> {
+    let worksheet = [
+      "100   7   9  17 100",
+      " 30   2  12   5   5",
+      "  5   0   4   3   2",
+      "-   max min %   /  ",
+    ]
+    .join("\n");
+
+    assert_eq!(answers_sum(&mut worksheet.as_bytes())?, 65 + 7 + 4 + 2 + 10);
+    Ok(())
+  }
+
+  #[test]
+  fn overflow() -> Result<()> {
+    let worksheet = "4294967296\n4294967296\n*";
+
+    assert!(matches!(
+      answers_sum(&mut worksheet.as_bytes()),
+      Err(Error::Arithmetic(what)) if what == "4294967296 * 4294967296 overflows or divides by zero"
+    ));
+    assert_eq!(
+      big_answers_sum(&mut worksheet.as_bytes(), Reading::LeftToRight)?,
+      BigInt::from(1u128 << 64)
+    );
+    assert_eq!(
+      big_answers_sum(&mut "3\n5\n-".as_bytes(), Reading::LeftToRight)?,
+      BigInt::from(-2)
+    );
+    assert!(matches!(
+      answers_sum(&mut "3\n0\n%".as_bytes()),
+      Err(Error::Arithmetic(_))
+    ));
+    assert!(matches!(
+      big_answers_sum(&mut "3\n0\n/".as_bytes(), Reading::LeftToRight),
+      Err(Error::Arithmetic(_))
+    ));
+    Ok(())
+  }
+
+  #[test]
+  fn sample_equations() -> Result<()> {
+    let ltr = equations(&mut read("./src/day06/sample.input")?, Reading::LeftToRight)?;
+    let rtl = equations(&mut read("./src/day06/sample.input")?, Reading::RightToLeft)?;
+
+    assert_eq!(
+      ltr[0],
+      Equation {
+        index: 0,
+        columns: 0..3,
+        operands: vec![123, 45, 6],
+        operator: Operator::Multiply,
+        result: 33210,
+      }
+    );
+    assert_eq!(
+      ltr.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+      [
+        "123 * 45 * 6 = 33210",
+        "328 + 64 + 98 = 490",
+        "51 * 387 * 215 = 4243455",
+        "64 + 23 + 314 = 401",
+      ]
+    );
+    assert_eq!(rtl[3].to_string(), "4 + 431 + 623 = 1058");
+    assert_eq!(rtl[3].columns, 12..15);
+    Ok(())
   }
 }
@@ -1,92 +1,122 @@
-use std::{collections::HashMap, io::BufRead};
+use std::{fmt, io::BufRead};
 
-use crate::{CartesianGrid, Coords, GridCoords, read_input};
+use crate::{
+  CartesianGrid, Coords, Edges, Error, FromCell, ICoords, ParseError, Result, read_input,
+  solver::{Answer, Solver},
+};
 
-pub fn how_many_beam_splits(input: &mut dyn BufRead) -> usize {
-  let lines = read_input(input);
-  let mut diagram = CartesianGrid::from(lines);
+const DOWN: [ICoords; 1] = [ICoords(0, 1)];
+const SIDES: [ICoords; 2] = [ICoords(-1, 0), ICoords(1, 0)];
+
+pub fn how_many_beam_splits(input: &mut dyn BufRead) -> Result<usize> {
+  let (mut diagram, _) = parse_diagram(read_input(input)?)?;
   let mut splits = 0;
 
   (0..diagram.height()).for_each(|y| splits += diagram.move_beams(&y));
 
-  diagram.print();
+  log::debug!("Beams after {} splits:\n{}", splits, diagram);
 
-  splits
+  Ok(splits)
 }
 
-fn how_many_different_timelines(input: &mut dyn BufRead) -> usize {
-  let lines = read_input(input);
-  let mut diagram = CartesianGrid::from(lines);
+/// Number of timelines reaching the bottom row. Each column carries the
+/// timelines whose beam is in it; a splitter sends its column's timelines to
+/// both sides and a beam leaving the diagram is lost.
+pub fn how_many_different_timelines(input: &mut dyn BufRead) -> Result<u128> {
+  let (diagram, start) = parse_diagram(read_input(input)?)?;
+  let width = diagram.width();
+  let overflow = || Error::Arithmetic("timeline count overflows u128".to_string());
 
-  (0..diagram.height()).for_each(|y| {
-    diagram.move_beams(&y);
-  });
+  let mut timelines = vec![0u128; width];
+  timelines[start.0] = 1;
 
-  let mut map: HashMap<Coords, usize> = HashMap::<Coords, usize>::new();
+  for y in start.1 + 1..diagram.height() {
+    let row = diagram.row(y);
+    let mut next = vec![0u128; width];
 
-  (0..diagram.height() - 1).rev().for_each(|y| {
-    diagram
-      .coords_at_y(y)
+    for (x, &count) in timelines
       .iter()
-      .filter(|c| *diagram.get(c) == '|')
-      .for_each(|c| {
-        map.entry(*c).or_insert(1);
-      });
+      .enumerate()
+      .filter(|(_, count)| **count > 0)
+    {
+      let targets = if row[x] == Cell::Splitter {
+        [x.checked_sub(1), Some(x + 1).filter(|&right| right < width)]
+      } else {
+        [Some(x), None]
+      };
+
+      for target in targets.into_iter().flatten() {
+        next[target] = next[target].checked_add(count).ok_or_else(overflow)?;
+      }
+    }
+    timelines = next;
+  }
 
-    diagram
-      .coords_at_y(y)
-      .iter()
-      .filter(|c| *diagram.get(c) == '|')
-      .for_each(|c| {
-        let this = *map.get(c).unwrap();
-
-        if let Some(splitter) = c
-          .sub_x(1)
-          .to_coords()
-          .filter(|l| l.in_grid(&diagram))
-          .filter(|l| *diagram.get(l) == '^')
-        {
-          map
-            .entry(splitter)
-            .and_modify(|count| *count += this)
-            .or_insert(this);
-        }
+  timelines
+    .iter()
+    .try_fold(0u128, |total, &count| total.checked_add(count))
+    .ok_or_else(overflow)
+}
 
-        if let Some(splitter) = c
-          .add_x(1)
-          .to_coords()
-          .filter(|r| r.in_grid(&diagram))
-          .filter(|r| *diagram.get(r) == '^')
-        {
-          map
-            .entry(splitter)
-            .and_modify(|count| *count += this)
-            .or_insert(this);
-        }
-      });
+pub struct Day07;
 
-    diagram
-      .coords_at_y(y)
-      .iter()
-      .filter(|c| *diagram.get(c) == '|' || *diagram.get(c) == '^')
-      .for_each(|c| {
-        println!("From {:?} to {:?}", c, c.sub_y(1));
-        if let Some(beam) = c.sub_y(1).to_coords().filter(|above| {
-          *diagram.get(above) == '|' || *diagram.get(above) == '^' || *diagram.get(above) == 'S'
-        }) {
-          let this = *map.get(c).unwrap();
-          map
-            .entry(beam)
-            .and_modify(|count| *count += this)
-            .or_insert(this);
-        }
-      });
-  });
+impl Solver for Day07 {
+  fn part1(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(how_many_beam_splits(input)?.into())
+  }
+
+  fn part2(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(how_many_different_timelines(input)?.into())
+  }
+}
+
+/// The diagram and where its beam starts.
+fn parse_diagram(lines: Vec<String>) -> Result<(CartesianGrid<Cell>, Coords), ParseError> {
+  let diagram = CartesianGrid::parse(&lines)?;
+
+  match diagram.find_one_coords(Cell::Start) {
+    Some(start) => Ok((diagram, start)),
+    None => Err(ParseError::new(
+      diagram.height() + 1,
+      1,
+      "a beam start `S`",
+      "",
+    )),
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Cell {
+  Empty,
+  Splitter,
+  Beam,
+  Start,
+}
 
-  *diagram
-    .find_one_coords('S')
-    .map(|c| map.get(&c).unwrap())
-    .unwrap()
+impl FromCell for Cell {
+  const EXPECTED: &'static str = "`.`, `^`, `|` or `S`";
+
+  fn from_cell(c: char) -> Option<Self> {
+    match c {
+      '.' => Some(Cell::Empty),
+      '^' => Some(Cell::Splitter),
+      '|' => Some(Cell::Beam),
+      'S' => Some(Cell::Start),
+      _ => None,
+    }
+  }
+}
+
+impl fmt::Display for Cell {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let c = match self {
+      Cell::Empty => '.',
+      Cell::Splitter => '^',
+      Cell::Beam => '|',
+      Cell::Start => 'S',
+    };
+    write!(f, "{}", c)
+  }
 }
 
 trait BeamDiagram {
@@ -95,7 +125,7 @@ trait BeamDiagram {
   fn split_beam(&mut self, src: &Coords) -> usize;
 }
 
-impl BeamDiagram for CartesianGrid<char> {
+impl BeamDiagram for CartesianGrid<Cell> {
   fn move_beams(&mut self, y: &usize) -> usize {
     let mut splits = 0;
 
@@ -103,15 +133,16 @@ impl BeamDiagram for CartesianGrid<char> {
       let u = self.get(&c);
 
       splits += match u {
-        'S' => {
-          self.set(&c.add_y(1).to_coords().unwrap(), '|');
+        Cell::Start => {
+          for downstream in self.neighbours(c, &DOWN, Edges::Bounded) {
+            self.set(&downstream, Cell::Beam);
+          }
           0
         }
-        '|' => c
-          .add_y(1)
-          .to_coords()
+        Cell::Beam => self
+          .neighbours(c, &DOWN, Edges::Bounded)
           .map(|downstream| self.move_beam(&downstream))
-          .unwrap_or(0),
+          .sum(),
         _ => 0,
       }
     }
@@ -120,16 +151,12 @@ impl BeamDiagram for CartesianGrid<char> {
   }
 
   fn move_beam(&mut self, c: &Coords) -> usize {
-    if !c.in_grid(self) {
-      return 0;
-    }
-
     let u = self.get(c);
 
     match u {
-      '^' => self.split_beam(c),
-      '.' => {
-        self.set(c, '|');
+      Cell::Splitter => self.split_beam(c),
+      Cell::Empty => {
+        self.set(c, Cell::Beam);
         0
       }
       _ => 0,
@@ -137,16 +164,9 @@ impl BeamDiagram for CartesianGrid<char> {
   }
 
   fn split_beam(&mut self, src: &Coords) -> usize {
-    src
-      .sub_x(1)
-      .to_coords()
-      .map(|left| self.set(&left, '|'))
-      .unwrap_or({});
-    src
-      .add_x(1)
-      .to_coords()
-      .map(|left| self.set(&left, '|'))
-      .unwrap_or({});
+    for side in self.neighbours(*src, &SIDES, Edges::Bounded) {
+      self.set(&side, Cell::Beam);
+    }
     1
   }
 }
@@ -154,39 +174,68 @@ impl BeamDiagram for CartesianGrid<char> {
 #[cfg(test)]
 mod tests {
   use crate::{
+    Error, ParseError, Result,
     day07::{how_many_beam_splits, how_many_different_timelines},
     read,
   };
 
   #[test]
-  fn sample_part1_input() {
+  fn sample_part1_input() -> Result<()> {
     assert_eq!(
-      how_many_beam_splits(&mut read("./src/day07/sample.input")),
+      how_many_beam_splits(&mut read("./src/day07/sample.input")?)?,
       21
     );
+    Ok(())
   }
 
   #[test]
-  fn my_part1_input() {
+  fn my_part1_input() -> Result<()> {
     assert_eq!(
-      how_many_beam_splits(&mut read("./src/day07/my.input")),
+      how_many_beam_splits(&mut read("./src/day07/my.input")?)?,
       1690
     );
+    Ok(())
   }
 
   #[test]
-  fn sample_part2_input() {
+  fn sample_part2_input() -> Result<()> {
     assert_eq!(
-      how_many_different_timelines(&mut read("./src/day07/sample.input")),
+      how_many_different_timelines(&mut read("./src/day07/sample.input")?)?,
       40
     );
+    Ok(())
   }
 
   #[test]
-  fn my_part2_input() {
+  fn my_part2_input() -> Result<()> {
     assert_eq!(
-      how_many_different_timelines(&mut read("./src/day07/my.input")),
+      how_many_different_timelines(&mut read("./src/day07/my.input")?)?,
       221371496188107
     );
+    Ok(())
+  }
+
+  #[test]
+  fn missing_start() {
+    assert!(matches!(
+      how_many_beam_splits(&mut "...\n.^.".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(3, 1, "a beam start `S`", "")
+    ));
+  }
+
+  #[test]
+  fn unknown_cell() {
+    assert!(matches!(
+      how_many_beam_splits(&mut "..S..\n..^.#".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(2, 5, "`.`, `^`, `|` or `S`", "#")
+    ));
+  }
+
+  #[test]
+  fn beams_leaving_the_diagram() -> Result<()> {
+    let diagram = "S..\n^..\n.^.\n...";
+
+    assert_eq!(how_many_different_timelines(&mut diagram.as_bytes())?, 2);
+    Ok(())
   }
 }
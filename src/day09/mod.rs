@@ -3,47 +3,72 @@ use std::{cmp, io::BufRead};
 use itertools::Itertools;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
-use crate::read_input;
-
-pub fn area_of_largest_rectangle(input: &mut dyn BufRead) -> usize {
-  let polygon = read_polygon(input);
+use crate::{
+  ParseError, Result,
+  error::Field,
+  point::Point,
+  read_input,
+  solver::{Answer, Solver},
+};
+
+/// A red tile's position.
+type Tile = Point<usize, 2>;
+
+pub fn area_of_largest_rectangle(input: &mut dyn BufRead) -> Result<usize> {
+  let polygon = read_polygon(input)?;
+
+  Ok(
+    make_unique_pairs(&polygon)
+      .iter()
+      .map(|pair| area(pair.0, pair.1))
+      .max()
+      .unwrap_or(0),
+  )
+}
 
-  make_unique_pairs(&polygon)
-    .iter()
-    .map(|pair| area(pair.0, pair.1))
-    .max()
-    .unwrap()
+pub fn area_of_largest_red_green_rectangle(input: &mut dyn BufRead) -> Result<usize> {
+  let coords = read_polygon(input)?;
+
+  Ok(
+    make_unique_pairs(&coords)
+      .par_iter()
+      .filter(|p| is_rectangle_red_green(p, &coords))
+      .map(|pair| area(pair.0, pair.1))
+      .max()
+      .unwrap_or(0),
+  )
 }
 
-pub fn area_of_largest_red_green_rectangle(input: &mut dyn BufRead) -> usize {
-  let coords = read_polygon(input);
+pub struct Day09;
 
-  make_unique_pairs(&coords)
-    .par_iter()
-    .filter(|p| is_rectangle_red_green(p, &coords))
-    .map(|p| p)
-    .map(|pair| area(pair.0, pair.1))
-    .max()
-    .unwrap()
+impl Solver for Day09 {
+  fn part1(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(area_of_largest_rectangle(input)?.into())
+  }
+
+  fn part2(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(area_of_largest_red_green_rectangle(input)?.into())
+  }
 }
 
-fn read_polygon(input: &mut dyn BufRead) -> Vec<Coords> {
-  let lines = read_input(input);
-  parse_coords(lines)
+fn read_polygon(input: &mut dyn BufRead) -> Result<Vec<Tile>> {
+  let lines = read_input(input)?;
+  Ok(parse_coords(&lines)?)
 }
 
-fn parse_coords(lines: Vec<String>) -> Vec<Coords> {
-  lines
-    .iter()
-    .map(|line| line.split_once(','))
-    .map(|split| Coords {
-      x: split.unwrap().0.parse().unwrap(),
-      y: split.unwrap().1.parse().unwrap(),
+fn parse_coords(lines: &[String]) -> Result<Vec<Tile>, ParseError> {
+  Field::lines(lines)
+    .map(|line| {
+      let (x, y) = line.split_once(',')?;
+      Ok(Point::new([
+        x.parse("an X coordinate")?,
+        y.parse("a Y coordinate")?,
+      ]))
     })
-    .collect_vec()
+    .collect()
 }
 
-fn make_unique_pairs(coords: &Vec<Coords>) -> Vec<(Coords, Coords)> {
+fn make_unique_pairs(coords: &[Tile]) -> Vec<(Tile, Tile)> {
   coords
     .iter()
     .enumerate()
@@ -57,30 +82,33 @@ fn make_unique_pairs(coords: &Vec<Coords>) -> Vec<(Coords, Coords)> {
     .collect_vec()
 }
 
-fn area(a: Coords, b: Coords) -> usize {
-  (1 + cmp::max(a.x, b.x) - cmp::min(a.x, b.x)) * (1 + cmp::max(a.y, b.y) - cmp::min(a.y, b.y))
+fn area(a: Tile, b: Tile) -> usize {
+  a.axis_distances(&b)
+    .iter()
+    .map(|d| 1 + *d as usize)
+    .product()
 }
 
-fn is_rectangle_red_green(c: &(Coords, Coords), coords: &Vec<Coords>) -> bool {
+fn is_rectangle_red_green(c: &(Tile, Tile), coords: &[Tile]) -> bool {
   let c1 = c.0;
   let c3 = c.1;
 
-  let c2 = Coords { x: c1.x, y: c3.y };
-  let c4 = Coords { x: c3.x, y: c1.y };
+  let c2 = Point::new([c1.x(), c3.y()]);
+  let c4 = Point::new([c3.x(), c1.y()]);
 
-  let vertices_red_green = vec![c2, c4].iter().all(|s| is_inside_polygon(s, coords));
+  let vertices_red_green = [c2, c4].iter().all(|s| is_inside_polygon(s, coords));
 
   let edges_red_green = vertices_red_green
-    && vec![(c1, c2), (c2, c3), (c3, c4), (c4, c1)]
+    && [(c1, c2), (c2, c3), (c3, c4), (c4, c1)]
       .iter()
       .all(|(start, end)| {
         coords_between(start, end)
           .iter()
-          .all(|p| is_inside_polygon(&p, coords))
+          .all(|p| is_inside_polygon(p, coords))
       });
 
   if edges_red_green {
-    println!(
+    log::trace!(
       "Found red-green rectangle: {:?} to {:?} = {}",
       c2,
       c4,
@@ -91,7 +119,7 @@ fn is_rectangle_red_green(c: &(Coords, Coords), coords: &Vec<Coords>) -> bool {
   edges_red_green
 }
 
-fn is_inside_polygon(point: &Coords, polygon: &Vec<Coords>) -> bool {
+fn is_inside_polygon(point: &Tile, polygon: &[Tile]) -> bool {
   let mut intersections = 0;
   let n = polygon.len();
 
@@ -99,7 +127,7 @@ fn is_inside_polygon(point: &Coords, polygon: &Vec<Coords>) -> bool {
     let p1 = &polygon[i];
     let p2 = &polygon[(i + 1) % n];
 
-    if is_on_edge(point, &p1, &p2) {
+    if is_on_edge(point, p1, p2) {
       return true;
     }
 
@@ -111,89 +139,92 @@ fn is_inside_polygon(point: &Coords, polygon: &Vec<Coords>) -> bool {
   intersections % 2 == 1
 }
 
-fn is_on_edge(point: &Coords, p1: &Coords, p2: &Coords) -> bool {
-  let Coords { x, y } = *point;
+fn is_on_edge(point: &Tile, p1: &Tile, p2: &Tile) -> bool {
+  let ([x, y], [x1, y1], [x2, y2]) = (point.0, p1.0, p2.0);
 
-  (p1.x == p2.x && x == p1.x && ((p1.y <= y && y <= p2.y) || (p2.y <= y && y <= p1.y)))
-    || (p1.y == p2.y && y == p1.y && ((p1.x <= x && x <= p2.x) || (p2.x <= x && x <= p1.x)))
+  (x1 == x2 && x == x1 && ((y1 <= y && y <= y2) || (y2 <= y && y <= y1)))
+    || (y1 == y2 && y == y1 && ((x1 <= x && x <= x2) || (x2 <= x && x <= x1)))
 }
 
-fn intersects_edge(point: &Coords, p1: &Coords, p2: &Coords) -> bool {
-  let Coords { x, y } = *point;
+fn intersects_edge(point: &Tile, p1: &Tile, p2: &Tile) -> bool {
+  let ([x, y], [x1, y1], [x2, y2]) = (point.0, p1.0, p2.0);
 
-  y > cmp::min(p1.y, p2.y) && y <= cmp::max(p1.y, p2.y) && x < cmp::max(p1.x, p2.x) && p1.x == p2.x
+  y > cmp::min(y1, y2) && y <= cmp::max(y1, y2) && x < cmp::max(x1, x2) && x1 == x2
 }
 
-fn coords_between(start: &Coords, end: &Coords) -> Vec<Coords> {
-  (cmp::min(start.y, end.y)..=cmp::max(start.y, end.y))
+fn coords_between(start: &Tile, end: &Tile) -> Vec<Tile> {
+  (cmp::min(start.y(), end.y())..=cmp::max(start.y(), end.y()))
     .flat_map(|y| {
-      (cmp::min(start.x, end.x)..=cmp::max(start.x, end.x)).map(move |x| Coords { x, y })
+      (cmp::min(start.x(), end.x())..=cmp::max(start.x(), end.x())).map(move |x| Point::new([x, y]))
     })
     .collect()
 }
 
-#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
-struct Coords {
-  x: usize,
-  y: usize,
-}
-
 #[cfg(test)]
 mod tests {
   use crate::{
-    day09::{
-      Coords, area_of_largest_rectangle, area_of_largest_red_green_rectangle, is_inside_polygon,
-    },
+    Error, ParseError, Result,
+    day09::{area_of_largest_rectangle, area_of_largest_red_green_rectangle, is_inside_polygon},
+    point::Point,
     read,
   };
 
   #[test]
-  fn sample_part1_input() {
+  fn sample_part1_input() -> Result<()> {
     assert_eq!(
-      area_of_largest_rectangle(&mut read("./src/day09/sample.input")),
+      area_of_largest_rectangle(&mut read("./src/day09/sample.input")?)?,
       50
     );
+    Ok(())
   }
 
   #[test]
-  fn my_part1_input() {
+  fn my_part1_input() -> Result<()> {
     assert_eq!(
-      area_of_largest_rectangle(&mut read("./src/day09/my.input")),
+      area_of_largest_rectangle(&mut read("./src/day09/my.input")?)?,
       4737096935
     );
+    Ok(())
   }
 
   #[test]
-  fn sample_part2_input() {
+  fn sample_part2_input() -> Result<()> {
     assert_eq!(
-      area_of_largest_red_green_rectangle(&mut read("./src/day09/sample.input")),
+      area_of_largest_red_green_rectangle(&mut read("./src/day09/sample.input")?)?,
       24
     );
+    Ok(())
   }
 
   #[test]
-  fn my_part2_input() {
+  fn my_part2_input() -> Result<()> {
     assert_eq!(
-      area_of_largest_red_green_rectangle(&mut read("./src/day09/my.input")),
+      area_of_largest_red_green_rectangle(&mut read("./src/day09/my.input")?)?,
       1644094530
     );
+    Ok(())
   }
 
   #[test]
   fn inside_polygon() {
-    assert_eq!(
-      is_inside_polygon(
-        &Coords { x: 3, y: 2 },
-        &vec![
-          Coords { x: 2, y: 0 },
-          Coords { x: 5, y: 0 },
-          Coords { x: 5, y: 2 },
-          Coords { x: 7, y: 2 },
-          Coords { x: 7, y: 4 },
-          Coords { x: 2, y: 4 },
-        ]
-      ),
-      true
-    );
+    assert!(is_inside_polygon(
+      &Point::new([3, 2]),
+      &[
+        Point::new([2, 0]),
+        Point::new([5, 0]),
+        Point::new([5, 2]),
+        Point::new([7, 2]),
+        Point::new([7, 4]),
+        Point::new([2, 4]),
+      ]
+    ));
+  }
+
+  #[test]
+  fn invalid_red_tile() {
+    assert!(matches!(
+      area_of_largest_rectangle(&mut "7,1\n11;1".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(2, 5, "`,`", "")
+    ));
   }
 }
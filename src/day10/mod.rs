@@ -1,102 +1,482 @@
-use std::{collections::HashSet, io::BufRead};
+use std::{
+  collections::{HashMap, HashSet},
+  io::BufRead,
+};
 
-use crate::{RemoveFirst, read_input};
+use itertools::Itertools;
 
-fn fewest_button_presses(input: &mut dyn BufRead) -> usize {
-  let lines = read_input(input);
-  let machines = parse_machines(&lines);
+use crate::{
+  Error, ParseError, Result,
+  bitset::BitSet,
+  error::Field,
+  read_input, search,
+  solver::{Answer, Solver},
+};
 
-  machines.iter().map(|m| m.fewest_button_presses()).sum()
+pub fn fewest_button_presses(input: &mut dyn BufRead) -> Result<usize> {
+  let lines = read_input(input)?;
+  let machines = parse_machines(&lines)?;
+
+  machines.iter().map(Machine::fewest_button_presses).sum()
+}
+
+pub fn fewest_button_presses_for_joltage(input: &mut dyn BufRead) -> Result<usize> {
+  let lines = read_input(input)?;
+  let machines = parse_machines(&lines)?;
+
+  machines
+    .iter()
+    .map(Machine::fewest_button_presses_for_joltage)
+    .sum()
 }
 
-fn parse_machines(lines: &Vec<String>) -> Vec<Machine> {
-  lines.iter().map(|l| parse_machine(l)).collect()
+fn no_solution(line: usize) -> Error {
+  Error::NoSolution(format!("machine on line {}", line))
+}
+
+pub struct Day10;
+
+impl Solver for Day10 {
+  fn part1(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(fewest_button_presses(input)?.into())
+  }
+
+  fn part2(&self, input: &mut dyn BufRead) -> Result<Answer> {
+    Ok(fewest_button_presses_for_joltage(input)?.into())
+  }
+}
+
+fn parse_machines(lines: &[String]) -> Result<Vec<Machine>, ParseError> {
+  let re = regex::Regex::new(
+    r"^\[(?<lights>[\.#]+)\](?<buttons>( \([0-9,]+\))+) \{(?<joltages>[0-9,]+)\}$",
+  )
+  .unwrap();
+
+  Field::lines(lines).map(|l| parse_machine(&re, l)).collect()
 }
 
-fn parse_machine(line: &String) -> Machine {
-  let re =
-    regex::Regex::new(r"\[(?<lights>[\.#]+)\](?<buttons>( \([0-9,]+\))+) \{(?<joltages>[0-9,]+)\}")
-      .unwrap();
-  let captures = re.captures(line).unwrap();
-  let indicator_light_diagram: Vec<bool> = captures["lights"].chars().map(|c| c == '#').collect();
-  let button_wiring_schematics: Vec<Vec<u32>> = captures["buttons"]
-    .trim()
+fn parse_machine(re: &regex::Regex, line: Field) -> Result<Machine, ParseError> {
+  let captures = re
+    .captures(line.text)
+    .ok_or(line.error("a machine `[lights] (buttons) {joltages}`"))?;
+  let capture = |name| {
+    let m = captures.name(name).unwrap();
+    line.slice(m.start(), m.end())
+  };
+
+  let lights = capture("lights");
+  let n = lights.text.len();
+  let indicator_light_diagram: BitSet = lights.text.match_indices('#').map(|(i, _)| i).collect();
+
+  let buttons = capture("buttons");
+  let button_wiring_schematics: Vec<BitSet> = buttons
     .split(' ')
+    .skip(1)
     .map(|b| {
-      let nums: Vec<u32> = b[1..b.len() - 1]
+      b.slice(1, b.text.len() - 1)
         .split(',')
-        .map(|n| n.parse::<u32>().unwrap())
-        .collect();
-      nums
+        .map(|light| {
+          let index = light.parse::<usize>("a light index")?;
+          if index < n {
+            Ok(index)
+          } else {
+            Err(light.error(&format!("a light index below {}", n)))
+          }
+        })
+        .collect()
     })
-    .collect();
-  let joltage_requirements: Vec<u32> = captures["joltages"]
+    .collect::<Result<_, ParseError>>()?;
+  let joltages = capture("joltages");
+  let joltage_requirements: Vec<u32> = joltages
     .split(',')
-    .map(|n| n.parse::<u32>().unwrap())
-    .collect();
-
-  let n = indicator_light_diagram.len() as u32;
-
-  Machine {
-    indicator_light_diagram: indicator_light_diagram
-      .iter()
-      .fold(0u16, |acc, &b| (acc << 1) | if b { 1 } else { 0 }),
-    button_wiring_schematics: button_wiring_schematics
-      .iter()
-      .map(|btn| btn.iter().fold(0u16, |acc, &b| acc + 2_u16.pow(n - b - 1)))
-      .collect(),
-    joltage_requirements,
+    .map(|j| j.parse("a joltage requirement"))
+    .collect::<Result<_, ParseError>>()?;
+
+  if joltage_requirements.len() != n {
+    return Err(joltages.error(&format!("{} joltage requirements", n)));
   }
+
+  Ok(Machine {
+    line: line.line,
+    lights: n,
+    indicator_light_diagram,
+    button_wiring_schematics,
+    joltage_requirements,
+  })
 }
 
+/// Largest null space whose `2^n` combinations part 1 enumerates.
+const MAX_FREE_BUTTONS: usize = 28;
+
+/// Largest rank whose `2^n` reachable light states part 1 searches
+/// breadth-first instead, all of them held in memory.
+const MAX_SEARCHED_RANK: usize = 20;
+
+/// Most buttons whose `2^n` subsets part 2 groups by parity.
+const MAX_JOLTAGE_BUTTONS: usize = 20;
+
+/// Increments of each subset of buttons with a given parity, and the fewest
+/// presses that give them.
+type ByParity = HashMap<BitSet, HashMap<Vec<u32>, usize>>;
+
 struct Machine {
-  indicator_light_diagram: u16,
-  button_wiring_schematics: Vec<u16>,
+  line: usize,
+  lights: usize,
+  indicator_light_diagram: BitSet,
+  button_wiring_schematics: Vec<BitSet>,
   joltage_requirements: Vec<u32>,
 }
 
 impl Machine {
-  fn fewest_button_presses(&self) -> usize {
-    let mut visited_lights = HashSet::<u16>::new();
-    let initial_lights = 0u16;
-    let mut queue: Vec<(u16, usize)> = vec![(initial_lights, 0)];
-
-    while let Some((current_lights, presses)) = queue.remove_first() {
-      if current_lights == self.indicator_light_diagram {
-        return presses;
+  /// Pressing a button twice cancels out, so the presses solve
+  /// `A x = indicator_light_diagram` over GF(2). Gaussian elimination yields
+  /// one solution and a null space basis; the fewest presses is the lightest
+  /// solution among all their combinations. When there are more combinations
+  /// than reachable light states, a breadth-first search over the states is
+  /// cheaper. Fails with [`Error::NoSolution`] when the lights cannot be
+  /// reached, and with [`Error::SearchLimit`] when neither space is small
+  /// enough to search.
+  fn fewest_button_presses(&self) -> Result<usize> {
+    let buttons = self.button_wiring_schematics.len();
+    let mut rows = (0..self.lights)
+      .map(|light| {
+        let mut row: BitSet = self
+          .button_wiring_schematics
+          .iter()
+          .positions(|b| b.contains(light))
+          .collect();
+        if self.indicator_light_diagram.contains(light) {
+          row.insert(buttons);
+        }
+        row
+      })
+      .collect_vec();
+
+    let mut pivots = vec![];
+    for column in 0..buttons {
+      let rank = pivots.len();
+      let Some(pivot) = (rank..rows.len()).find(|&r| rows[r].contains(column)) else {
+        continue;
+      };
+      rows.swap(rank, pivot);
+      let pivot_row = rows[rank].clone();
+      for (r, row) in rows.iter_mut().enumerate() {
+        if r != rank && row.contains(column) {
+          *row ^= &pivot_row;
+        }
       }
+      pivots.push(column);
+    }
+
+    if rows[pivots.len()..].iter().any(|row| row.contains(buttons)) {
+      return Err(no_solution(self.line));
+    }
 
-      for &button in &self.button_wiring_schematics {
-        let new_lights = current_lights ^ button;
+    let pivot_columns_set_in = |column: usize| -> BitSet {
+      pivots
+        .iter()
+        .enumerate()
+        .filter(|(r, _)| rows[*r].contains(column))
+        .map(|(_, &c)| c)
+        .collect()
+    };
+    let mut presses = pivot_columns_set_in(buttons);
+    let null_space = (0..buttons)
+      .filter(|c| !pivots.contains(c))
+      .map(|free| {
+        let mut v = pivot_columns_set_in(free);
+        v.insert(free);
+        v
+      })
+      .collect_vec();
 
-        if !visited_lights.contains(&new_lights) {
-          visited_lights.insert(new_lights);
-          queue.push((new_lights, presses + 1));
+    let rank = pivots.len();
+    if null_space.len() > rank && rank <= MAX_SEARCHED_RANK {
+      return self
+        .fewest_button_presses_by_search()
+        .ok_or_else(|| no_solution(self.line));
+    }
+    if null_space.len() > MAX_FREE_BUTTONS {
+      return Err(Error::SearchLimit(format!(
+        "machine on line {} has {} free buttons, at most {} can be combined",
+        self.line,
+        null_space.len(),
+        MAX_FREE_BUTTONS
+      )));
+    }
+
+    let mut fewest = presses.len();
+    for combination in 1..1usize << null_space.len() {
+      presses ^= &null_space[combination.trailing_zeros() as usize];
+      fewest = fewest.min(presses.len());
+    }
+
+    Ok(fewest)
+  }
+
+  /// Breadth-first search over light states, of which there are `2^rank`.
+  fn fewest_button_presses_by_search(&self) -> Option<usize> {
+    search::bfs(
+      BitSet::new(),
+      |lights| {
+        self
+          .button_wiring_schematics
+          .iter()
+          .map(|b| lights ^ b)
+          .collect_vec()
+      },
+      |lights| *lights == self.indicator_light_diagram,
+      &mut HashSet::new(),
+    )
+    .map(|path| path.steps())
+  }
+
+  /// Every press adds one to each wired counter, so pressing button `j`
+  /// `x_j` times must solve `A x = joltage_requirements` over non-negative
+  /// integers. Buttons pressed an odd number of times have to flip exactly
+  /// the counters with odd requirements (the part 1 problem); taking those
+  /// once leaves an all-even remainder that is half of a smaller instance.
+  ///
+  /// Subsets are visited in Gray code order, one button toggled at a time.
+  /// Machines with more than [`MAX_JOLTAGE_BUTTONS`] buttons fail with
+  /// [`Error::SearchLimit`].
+  fn fewest_button_presses_for_joltage(&self) -> Result<usize> {
+    let buttons = &self.button_wiring_schematics;
+    if buttons.len() > MAX_JOLTAGE_BUTTONS {
+      return Err(Error::SearchLimit(format!(
+        "machine on line {} has {} buttons, at most {} can be combined",
+        self.line,
+        buttons.len(),
+        MAX_JOLTAGE_BUTTONS
+      )));
+    }
+
+    let mut pressed = BitSet::new();
+    let mut parity = BitSet::new();
+    let mut increments = vec![0u32; self.lights];
+    let mut by_parity = ByParity::new();
+    by_parity
+      .entry(parity.clone())
+      .or_default()
+      .insert(increments.clone(), 0);
+
+    for subset in 1..1usize << buttons.len() {
+      let toggled = subset.trailing_zeros() as usize;
+      pressed.toggle(toggled);
+      parity ^= &buttons[toggled];
+      for c in buttons[toggled].iter() {
+        if pressed.contains(toggled) {
+          increments[c] += 1;
+        } else {
+          increments[c] -= 1;
         }
       }
+
+      let presses = by_parity
+        .entry(parity.clone())
+        .or_default()
+        .entry(increments.clone())
+        .or_insert(usize::MAX);
+      *presses = (*presses).min(pressed.len());
     }
 
-    panic!("No solution found");
+    fewest_presses_for(&self.joltage_requirements, &by_parity, &mut HashMap::new())
+      .ok_or_else(|| no_solution(self.line))
+  }
+}
+
+fn fewest_presses_for(
+  requirements: &[u32],
+  by_parity: &ByParity,
+  cache: &mut HashMap<Vec<u32>, Option<usize>>,
+) -> Option<usize> {
+  if requirements.iter().all(|&r| r == 0) {
+    return Some(0);
   }
+  if let Some(&presses) = cache.get(requirements) {
+    return presses;
+  }
+
+  let parity: BitSet = requirements.iter().positions(|r| r % 2 == 1).collect();
+
+  let presses = by_parity
+    .get(&parity)
+    .into_iter()
+    .flatten()
+    .filter(|(increments, _)| increments.iter().zip(requirements).all(|(i, r)| i <= r))
+    .filter_map(|(increments, &odd_presses)| {
+      let half = requirements
+        .iter()
+        .zip(increments)
+        .map(|(r, i)| (r - i) / 2)
+        .collect_vec();
+
+      fewest_presses_for(&half, by_parity, cache).map(|p| odd_presses + 2 * p)
+    })
+    .min();
+
+  cache.insert(requirements.to_vec(), presses);
+  presses
 }
 
 #[cfg(test)]
 mod tests {
-  use crate::{day10::fewest_button_presses, read};
+  use crate::{
+    Error, ParseError, Result,
+    day10::{fewest_button_presses, fewest_button_presses_for_joltage, parse_machines},
+    read, read_input,
+  };
 
   #[test]
-  fn sample_part1_input() {
+  fn sample_part1_input() -> Result<()> {
     assert_eq!(
-      fewest_button_presses(&mut read("./src/day10/sample.input")),
+      fewest_button_presses(&mut read("./src/day10/sample.input")?)?,
       7
     );
+    Ok(())
   }
 
   #[test]
-  fn my_part1_input() {
+  fn my_part1_input() -> Result<()> {
     assert_eq!(
-      fewest_button_presses(&mut read("./src/day10/my.input")),
+      fewest_button_presses(&mut read("./src/day10/my.input")?)?,
       461
     );
+    Ok(())
+  }
+
+  #[test]
+  fn sample_part2_input() -> Result<()> {
+    assert_eq!(
+      fewest_button_presses_for_joltage(&mut read("./src/day10/sample.input")?)?,
+      33
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn my_part2_input() -> Result<()> {
+    assert_eq!(
+      fewest_button_presses_for_joltage(&mut read("./src/day10/my.input")?)?,
+      16386
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn search_agrees_with_elimination() -> Result<()> {
+    let lines = read_input(&mut read("./src/day10/my.input")?)?;
+
+    for machine in parse_machines(&lines)? {
+      assert_eq!(
+        machine.fewest_button_presses_by_search(),
+        machine.fewest_button_presses().ok()
+      );
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn more_than_sixteen_lights() -> Result<()> {
+    let machine =
+      "[#..................#] (0) (19) (0,19) (1,2,18) {1,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1}";
+
+    assert_eq!(fewest_button_presses(&mut machine.as_bytes())?, 1);
+    assert_eq!(
+      fewest_button_presses_for_joltage(&mut machine.as_bytes())?,
+      1
+    );
+    Ok(())
+  }
+
+  /// A machine whose every light has two buttons of its own, so that both
+  /// the null space and the reachable states have one dimension per light.
+  fn paired_buttons(lights: usize) -> String {
+    let buttons = (0..lights)
+      .map(|l| format!(" ({}) ({})", l, l))
+      .collect::<String>();
+    format!(
+      "[{}]{} {{{}}}",
+      "#".repeat(lights),
+      buttons,
+      vec!["1"; lights].join(",")
+    )
+  }
+
+  #[test]
+  fn large_null_space_is_enumerated() -> Result<()> {
+    assert_eq!(
+      fewest_button_presses(&mut paired_buttons(25).as_bytes())?,
+      25
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn search_space_too_large() {
+    let lights = super::MAX_FREE_BUTTONS + 1;
+
+    assert!(matches!(
+      fewest_button_presses(&mut paired_buttons(lights).as_bytes()),
+      Err(Error::SearchLimit(what))
+        if what == "machine on line 1 has 29 free buttons, at most 28 can be combined"
+    ));
+  }
+
+  #[test]
+  fn many_free_buttons_are_searched() -> Result<()> {
+    let buttons = " (0)".repeat(super::MAX_FREE_BUTTONS + 2);
+
+    assert_eq!(
+      fewest_button_presses(&mut format!("[#]{} {{1}}", buttons).as_bytes())?,
+      1
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn too_many_joltage_buttons() -> Result<()> {
+    let buttons = |n| " (0)".repeat(n);
+
+    assert_eq!(
+      fewest_button_presses_for_joltage(
+        &mut format!("[#]{} {{3}}", buttons(super::MAX_JOLTAGE_BUTTONS)).as_bytes()
+      )?,
+      3
+    );
+    assert!(matches!(
+      fewest_button_presses_for_joltage(
+        &mut format!("[#]{} {{3}}", buttons(super::MAX_JOLTAGE_BUTTONS + 1)).as_bytes()
+      ),
+      Err(Error::SearchLimit(what))
+        if what == "machine on line 1 has 21 buttons, at most 20 can be combined"
+    ));
+    Ok(())
+  }
+
+  #[test]
+  fn unreachable_joltage() {
+    assert!(matches!(
+      fewest_button_presses_for_joltage(&mut "[##] (0,1) {1,2}".as_bytes()),
+      Err(Error::NoSolution(_))
+    ));
+  }
+
+  #[test]
+  fn invalid_machine() {
+    assert!(matches!(
+      fewest_button_presses(&mut "[.##.] (3) (1,3)".as_bytes()),
+      Err(Error::Parse(e)) if e.line == 1 && e.column == 1
+    ));
+    for junk in ["x [.##.] (3) {3,5,4,7}", "[.##.] (3) {3,5,4,7} x"] {
+      assert!(matches!(
+        fewest_button_presses(&mut junk.as_bytes()),
+        Err(Error::Parse(e)) if e.line == 1 && e.column == 1
+      ));
+    }
+    assert!(matches!(
+      fewest_button_presses(&mut "[.##.] (3) (1,4) {3,5,4,7}".as_bytes()),
+      Err(Error::Parse(e)) if e == ParseError::new(1, 15, "a light index below 4", "4")
+    ));
   }
 }
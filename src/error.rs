@@ -0,0 +1,218 @@
+use std::{fmt, io, str::FromStr};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum Error {
+  Io(io::Error),
+  Parse(ParseError),
+  NoSolution(String),
+  /// A calculation overflowed or divided by zero.
+  Arithmetic(String),
+  /// A solver was configured with a value it cannot work with.
+  InvalidArgument(String),
+  /// An input would need more work than a solver is willing to do.
+  SearchLimit(String),
+}
+
+/// Position of a malformed token in the puzzle input, both counted from 1.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+  pub line: usize,
+  pub column: usize,
+  pub expected: String,
+  pub found: String,
+}
+
+impl ParseError {
+  pub fn new(line: usize, column: usize, expected: &str, found: &str) -> Self {
+    ParseError {
+      line,
+      column,
+      expected: expected.to_string(),
+      found: found.to_string(),
+    }
+  }
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let found = if self.found.is_empty() {
+      "end of line".to_string()
+    } else {
+      format!("`{}`", self.found)
+    };
+
+    write!(
+      f,
+      "line {}, column {}: expected {}, found {}",
+      self.line, self.column, self.expected, found
+    )
+  }
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Error::Io(e) => write!(f, "{}", e),
+      Error::Parse(e) => write!(f, "{}", e),
+      Error::NoSolution(what) => write!(f, "no solution for {}", what),
+      Error::Arithmetic(what) => write!(f, "arithmetic error: {}", what),
+      Error::InvalidArgument(what) => write!(f, "invalid argument: {}", what),
+      Error::SearchLimit(what) => write!(f, "search limit exceeded: {}", what),
+    }
+  }
+}
+
+impl std::error::Error for ParseError {}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+  fn from(e: io::Error) -> Self {
+    Error::Io(e)
+  }
+}
+
+impl From<ParseError> for Error {
+  fn from(e: ParseError) -> Self {
+    Error::Parse(e)
+  }
+}
+
+/// A slice of one input line that remembers where it starts, so parse
+/// failures can point at the offending token.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Field<'a> {
+  pub line: usize,
+  pub column: usize,
+  pub text: &'a str,
+}
+
+impl<'a> Field<'a> {
+  pub fn line(index: usize, text: &'a str) -> Self {
+    Field {
+      line: index + 1,
+      column: 1,
+      text,
+    }
+  }
+
+  pub fn lines(lines: &'a [String]) -> impl DoubleEndedIterator<Item = Field<'a>> {
+    lines
+      .iter()
+      .enumerate()
+      .map(|(i, line)| Field::line(i, line))
+  }
+
+  pub fn slice(&self, start: usize, end: usize) -> Field<'a> {
+    Field {
+      line: self.line,
+      column: self.column + self.text[..start].chars().count(),
+      text: &self.text[start..end],
+    }
+  }
+
+  pub fn split(&self, separator: char) -> impl Iterator<Item = Field<'a>> + '_ {
+    self.text.split(separator).scan(0, move |start, part| {
+      let field = self.slice(*start, *start + part.len());
+      *start += part.len() + separator.len_utf8();
+      Some(field)
+    })
+  }
+
+  pub fn split_whitespace(&self) -> impl Iterator<Item = Field<'a>> + '_ {
+    self.text.split_ascii_whitespace().map(|part| {
+      let start = part.as_ptr() as usize - self.text.as_ptr() as usize;
+      self.slice(start, start + part.len())
+    })
+  }
+
+  pub fn split_once(&self, separator: char) -> Result<(Field<'a>, Field<'a>), ParseError> {
+    match self.text.find(separator) {
+      Some(i) => Ok((
+        self.slice(0, i),
+        self.slice(i + separator.len_utf8(), self.text.len()),
+      )),
+      None => Err(self.end().error(&format!("`{}`", separator))),
+    }
+  }
+
+  /// The field without leading and trailing whitespace.
+  pub fn trim(&self) -> Field<'a> {
+    let start = self.text.len() - self.text.trim_start().len();
+    let end = self.text.trim_end().len().max(start);
+    self.slice(start, end)
+  }
+
+  /// Empty field just past the end of this one, used to report missing tokens.
+  pub fn end(&self) -> Field<'a> {
+    self.slice(self.text.len(), self.text.len())
+  }
+
+  pub fn chars(&self) -> impl Iterator<Item = (Field<'a>, char)> + '_ {
+    self
+      .text
+      .char_indices()
+      .map(|(i, c)| (self.slice(i, i + c.len_utf8()), c))
+  }
+
+  pub fn parse<T: FromStr>(&self, expected: &str) -> Result<T, ParseError> {
+    self.text.parse::<T>().map_err(|_| self.error(expected))
+  }
+
+  pub fn error(&self, expected: &str) -> ParseError {
+    ParseError::new(self.line, self.column, expected, self.text)
+  }
+}
+
+/// Pulls the next field out of a split of `whole`, reporting a missing token
+/// just past the end of `whole`.
+pub(crate) fn next_field<'a>(
+  fields: &mut impl Iterator<Item = Field<'a>>,
+  whole: &Field<'a>,
+  expected: &str,
+) -> Result<Field<'a>, ParseError> {
+  fields.next().ok_or_else(|| whole.end().error(expected))
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::error::{Field, ParseError};
+
+  #[test]
+  fn split_keeps_columns() {
+    let line = Field::line(2, "12,x,7");
+    let fields = line
+      .split(',')
+      .map(|f| (f.column, f.text))
+      .collect::<Vec<_>>();
+
+    assert_eq!(fields, vec![(1, "12"), (4, "x"), (6, "7")]);
+  }
+
+  #[test]
+  fn parse_reports_position() {
+    let line = Field::line(2, "12,x,7");
+    let x = line.split(',').nth(1).unwrap();
+
+    assert_eq!(
+      x.parse::<u32>("a number"),
+      Err(ParseError::new(3, 4, "a number", "x"))
+    );
+    assert_eq!(
+      x.parse::<u32>("a number").unwrap_err().to_string(),
+      "line 3, column 4: expected a number, found `x`"
+    );
+  }
+
+  #[test]
+  fn missing_separator_points_past_the_end() {
+    let line = Field::line(0, "10");
+
+    assert_eq!(
+      line.split_once('-').unwrap_err(),
+      ParseError::new(1, 3, "`-`", "")
+    );
+  }
+}
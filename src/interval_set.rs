@@ -0,0 +1,231 @@
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+/// Integer type usable as an interval endpoint.
+pub trait Endpoint: Copy + Ord {
+  fn successor(self) -> Option<Self>;
+  fn predecessor(self) -> Option<Self>;
+  /// Number of values in `start..=end`.
+  fn span(start: Self, end: Self) -> u128;
+}
+
+macro_rules! endpoint {
+  ($($t:ty),*) => {
+    $(
+      impl Endpoint for $t {
+        fn successor(self) -> Option<Self> {
+          self.checked_add(1)
+        }
+
+        fn predecessor(self) -> Option<Self> {
+          self.checked_sub(1)
+        }
+
+        fn span(start: Self, end: Self) -> u128 {
+          end.abs_diff(start) as u128 + 1
+        }
+      }
+    )*
+  };
+}
+
+endpoint!(u32, u64, usize, i32, i64);
+
+/// Set of integers stored as sorted, disjoint, non-adjacent inclusive
+/// intervals keyed by their start.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct IntervalSet<T> {
+  intervals: BTreeMap<T, T>,
+}
+
+impl<T: Endpoint> IntervalSet<T> {
+  pub fn new() -> Self {
+    IntervalSet {
+      intervals: BTreeMap::new(),
+    }
+  }
+
+  /// Adds every value in `range`, merging it with the intervals it overlaps
+  /// or touches.
+  pub fn insert(&mut self, range: RangeInclusive<T>) {
+    let (mut start, mut end) = range.into_inner();
+    if start > end {
+      return;
+    }
+
+    if let Some((&s, &e)) = self.intervals.range(..=start).next_back()
+      && e.successor().is_none_or(|after| after >= start)
+    {
+      self.intervals.remove(&s);
+      start = s;
+      end = end.max(e);
+    }
+
+    while let Some((&s, &e)) = self.intervals.range(start..).next() {
+      if end.successor().is_some_and(|after| s > after) {
+        break;
+      }
+      self.intervals.remove(&s);
+      end = end.max(e);
+    }
+
+    self.intervals.insert(start, end);
+  }
+
+  pub fn contains(&self, value: T) -> bool {
+    self
+      .intervals
+      .range(..=value)
+      .next_back()
+      .is_some_and(|(_, &end)| value <= end)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.intervals.is_empty()
+  }
+
+  /// Number of values in the set.
+  pub fn len(&self) -> u128 {
+    self
+      .intervals
+      .iter()
+      .map(|(&start, &end)| T::span(start, end))
+      .sum()
+  }
+
+  /// The intervals in ascending order.
+  pub fn iter(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+    self.intervals.iter().map(|(&start, &end)| start..=end)
+  }
+
+  pub fn union(&self, other: &Self) -> Self {
+    let mut union = self.clone();
+    for range in other.iter() {
+      union.insert(range);
+    }
+    union
+  }
+
+  pub fn intersection(&self, other: &Self) -> Self {
+    let mut intersection = IntervalSet::new();
+    let mut others = other.iter().peekable();
+
+    for range in self.iter() {
+      while let Some(o) = others.peek() {
+        let start = *range.start().max(o.start());
+        let end = *range.end().min(o.end());
+        if start <= end {
+          intersection.insert(start..=end);
+        }
+        if o.end() > range.end() {
+          break;
+        }
+        others.next();
+      }
+    }
+    intersection
+  }
+
+  /// Values in `self` but not in `other`.
+  pub fn difference(&self, other: &Self) -> Self {
+    let mut difference = IntervalSet::new();
+    let mut others = other.iter().peekable();
+
+    for range in self.iter() {
+      let mut start = Some(*range.start());
+
+      while let (Some(s), Some(o)) = (start, others.peek()) {
+        if o.start() > range.end() {
+          break;
+        }
+        if *o.end() >= s {
+          if let Some(before) = o.start().predecessor().filter(|&b| b >= s) {
+            difference.insert(s..=before);
+          }
+          start = o.end().successor();
+        }
+        if o.end() > range.end() {
+          break;
+        }
+        others.next();
+      }
+
+      if let Some(s) = start.filter(|s| s <= range.end()) {
+        difference.insert(s..=*range.end());
+      }
+    }
+    difference
+  }
+}
+
+impl<T: Endpoint> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+  fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+    let mut set = IntervalSet::new();
+    for range in iter {
+      set.insert(range);
+    }
+    set
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeSet;
+
+  use proptest::prelude::*;
+
+  use crate::interval_set::IntervalSet;
+
+  #[test]
+  fn coalesces_overlapping_and_adjacent() {
+    let set = IntervalSet::from_iter([10..=14, 3..=5, 16..=20, 12..=18, 6..=7, 30..=30]);
+
+    assert_eq!(set.iter().collect::<Vec<_>>(), [3..=7, 10..=20, 30..=30]);
+    assert_eq!(set.len(), 17);
+    assert!(set.contains(15));
+    assert!(!set.contains(8));
+    assert!(!set.contains(31));
+  }
+
+  #[test]
+  fn full_range() {
+    let set = IntervalSet::from_iter([0..=u64::MAX, 5..=u64::MAX]);
+
+    assert_eq!(set.len(), 1 << 64);
+    assert!(set.contains(u64::MAX));
+    assert!(set.difference(&set).is_empty());
+  }
+
+  fn set_of(ranges: &[(u8, u8)]) -> (IntervalSet<u32>, BTreeSet<u32>) {
+    let ranges = ranges
+      .iter()
+      .map(|&(a, b)| (a.min(b) as u32, a.max(b) as u32))
+      .collect::<Vec<_>>();
+    (
+      ranges.iter().map(|&(s, e)| s..=e).collect(),
+      ranges.iter().flat_map(|&(s, e)| s..=e).collect(),
+    )
+  }
+
+  proptest! {
+    #[test]
+    fn matches_a_set_of_values(
+      a in prop::collection::vec(any::<(u8, u8)>(), 0..8),
+      b in prop::collection::vec(any::<(u8, u8)>(), 0..8),
+    ) {
+      let (a, a_values) = set_of(&a);
+      let (b, b_values) = set_of(&b);
+      let values = |set: &IntervalSet<u32>| set.iter().flatten().collect::<BTreeSet<_>>();
+
+      prop_assert_eq!(a.len(), a_values.len() as u128);
+      prop_assert_eq!(values(&a.union(&b)), &a_values | &b_values);
+      prop_assert_eq!(values(&a.intersection(&b)), &a_values & &b_values);
+      prop_assert_eq!(values(&a.difference(&b)), &a_values - &b_values);
+      for v in 0..=256 {
+        prop_assert_eq!(a.contains(v), a_values.contains(&v));
+      }
+      for pair in a.union(&b).iter().collect::<Vec<_>>().windows(2) {
+        prop_assert!(*pair[0].end() + 1 < *pair[1].start());
+      }
+    }
+  }
+}
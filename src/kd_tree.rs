@@ -0,0 +1,243 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::point::{Coordinate, Point};
+
+/// Static k-d tree over integer points, answering nearest neighbour queries
+/// incrementally: neighbours come out closest first, using exact squared
+/// Euclidean distances and breaking ties by point index. Distances too large
+/// for `u128` saturate at `u128::MAX`, so such points come last by index.
+pub struct KdTree<T, const K: usize> {
+  points: Vec<Point<T, K>>,
+  nodes: Vec<Node<T, K>>,
+}
+
+struct Node<T, const K: usize> {
+  point: usize,
+  children: [Option<usize>; 2],
+  min: Point<T, K>,
+  max: Point<T, K>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Entry {
+  // Subtrees sort before leaves at the same distance, so every point index at
+  // that distance is queued before the first of them is yielded.
+  Subtree(usize),
+  Leaf(usize),
+}
+
+impl<T: Coordinate, const K: usize> KdTree<T, K> {
+  pub fn new(points: Vec<Point<T, K>>) -> Self {
+    let mut tree = KdTree {
+      points,
+      nodes: vec![],
+    };
+    let mut indices = (0..tree.points.len()).collect::<Vec<_>>();
+    tree.build(&mut indices, 0);
+    tree
+  }
+
+  pub fn len(&self) -> usize {
+    self.points.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.points.is_empty()
+  }
+
+  fn build(&mut self, indices: &mut [usize], depth: usize) -> Option<usize> {
+    if indices.is_empty() {
+      return None;
+    }
+
+    let axis = depth % K;
+    let median = indices.len() / 2;
+    indices.select_nth_unstable_by_key(median, |&i| self.points[i][axis]);
+
+    let point = indices[median];
+    let mut min = self.points[point];
+    let mut max = self.points[point];
+    for &i in indices.iter() {
+      for a in 0..K {
+        min.0[a] = min[a].min(self.points[i][a]);
+        max.0[a] = max[a].max(self.points[i][a]);
+      }
+    }
+
+    let (left, rest) = indices.split_at_mut(median);
+    let children = [
+      self.build(left, depth + 1),
+      self.build(&mut rest[1..], depth + 1),
+    ];
+
+    self.nodes.push(Node {
+      point,
+      children,
+      min,
+      max,
+    });
+    Some(self.nodes.len() - 1)
+  }
+
+  fn root(&self) -> Option<usize> {
+    self.nodes.len().checked_sub(1)
+  }
+
+  /// All points ordered by distance from `query`, as `(squared distance, index)`.
+  pub fn nearest(&self, query: Point<T, K>) -> Nearest<'_, T, K> {
+    let mut queue = BinaryHeap::new();
+    if let Some(root) = self.root() {
+      queue.push(Reverse((
+        self.node_distance(root, &query),
+        Entry::Subtree(root),
+      )));
+    }
+
+    Nearest {
+      tree: self,
+      query,
+      queue,
+    }
+  }
+
+  /// Every unordered pair of points, closest first, as
+  /// `(squared distance, lower index, higher index)`.
+  pub fn closest_pairs(&self) -> ClosestPairs<'_, T, K> {
+    let mut neighbours = (0..self.len())
+      .map(|i| self.nearest(self.points[i]))
+      .collect::<Vec<_>>();
+    let mut queue = BinaryHeap::new();
+
+    for (i, n) in neighbours.iter_mut().enumerate() {
+      if let Some((d, j)) = n.find(|&(_, j)| j > i) {
+        queue.push(Reverse((d, i, j)));
+      }
+    }
+
+    ClosestPairs { neighbours, queue }
+  }
+
+  fn node_distance(&self, node: usize, query: &Point<T, K>) -> u128 {
+    let Node { min, max, .. } = &self.nodes[node];
+    let mut closest = *query;
+    for a in 0..K {
+      closest.0[a] = query[a].clamp(min[a], max[a]);
+    }
+    saturating_distance(query, &closest)
+  }
+}
+
+/// Squared Euclidean distance, saturating at `u128::MAX`.
+fn saturating_distance<T: Coordinate, const K: usize>(a: &Point<T, K>, b: &Point<T, K>) -> u128 {
+  a.squared_euclidean(b).unwrap_or(u128::MAX)
+}
+
+pub struct Nearest<'a, T, const K: usize> {
+  tree: &'a KdTree<T, K>,
+  query: Point<T, K>,
+  queue: BinaryHeap<Reverse<(u128, Entry)>>,
+}
+
+impl<T: Coordinate, const K: usize> Iterator for Nearest<'_, T, K> {
+  type Item = (u128, usize);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    while let Some(Reverse((distance, entry))) = self.queue.pop() {
+      match entry {
+        Entry::Leaf(point) => return Some((distance, point)),
+        Entry::Subtree(node) => {
+          let Node {
+            point, children, ..
+          } = &self.tree.nodes[node];
+          let d = saturating_distance(&self.tree.points[*point], &self.query);
+          self.queue.push(Reverse((d, Entry::Leaf(*point))));
+
+          for &child in children.iter().flatten() {
+            let d = self.tree.node_distance(child, &self.query);
+            self.queue.push(Reverse((d, Entry::Subtree(child))));
+          }
+        }
+      }
+    }
+    None
+  }
+}
+
+pub struct ClosestPairs<'a, T, const K: usize> {
+  neighbours: Vec<Nearest<'a, T, K>>,
+  queue: BinaryHeap<Reverse<(u128, usize, usize)>>,
+}
+
+impl<T: Coordinate, const K: usize> Iterator for ClosestPairs<'_, T, K> {
+  type Item = (u128, usize, usize);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let Reverse((d, i, j)) = self.queue.pop()?;
+
+    if let Some((next_d, next_j)) = self.neighbours[i].find(|&(_, j)| j > i) {
+      self.queue.push(Reverse((next_d, i, next_j)));
+    }
+
+    Some((d, i, j))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use itertools::Itertools;
+
+  use crate::{kd_tree::KdTree, point::Point};
+
+  fn points() -> Vec<Point<i64, 3>> {
+    (0..60)
+      .map(|i: i64| Point::new([(i * 37) % 11, (i * 17) % 7 - 3, (i * 5) % 13]))
+      .collect()
+  }
+
+  #[test]
+  fn nearest_matches_brute_force() {
+    let points = points();
+    let tree = KdTree::new(points.clone());
+    let query = Point::new([4, -1, 6]);
+
+    let expected = (0..points.len())
+      .map(|i| (points[i].squared_euclidean(&query).unwrap(), i))
+      .sorted()
+      .collect_vec();
+
+    assert_eq!(tree.nearest(query).collect_vec(), expected);
+  }
+
+  #[test]
+  fn closest_pairs_match_brute_force() {
+    let points = points();
+    let tree = KdTree::new(points.clone());
+
+    let expected = (0..points.len())
+      .tuple_combinations()
+      .map(|(i, j)| (points[i].squared_euclidean(&points[j]).unwrap(), i, j))
+      .sorted()
+      .collect_vec();
+
+    assert_eq!(tree.closest_pairs().collect_vec(), expected);
+  }
+
+  #[test]
+  fn overflowing_distances_come_last() {
+    let far = Point::new([u64::MAX, u64::MAX]);
+    let tree = KdTree::new(vec![far, far, Point::new([1, 1]), Point::origin()]);
+
+    assert_eq!(
+      tree.nearest(Point::origin()).collect_vec(),
+      [(0, 3), (2, 2), (u128::MAX, 0), (u128::MAX, 1)]
+    );
+  }
+
+  #[test]
+  fn empty_tree() {
+    let tree = KdTree::<i64, 2>::new(vec![]);
+
+    assert_eq!(tree.nearest(Point::origin()).count(), 0);
+    assert_eq!(tree.closest_pairs().count(), 0);
+  }
+}
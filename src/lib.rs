@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 use std::{
+  fmt,
   fs::File,
   io::{BufRead, BufReader},
   ops,
@@ -8,6 +9,14 @@ use std::{
 
 use itertools::Itertools;
 
+use crate::{
+  error::Field,
+  line::{Endpoints, Line},
+  point::Point,
+};
+
+pub mod automaton;
+pub mod bitset;
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -18,36 +27,77 @@ pub mod day07;
 pub mod day08;
 pub mod day09;
 pub mod day10;
-
-pub fn read_input(input: &mut dyn BufRead) -> Vec<String> {
-  input
-    .lines()
-    .map(|line| line.unwrap())
-    .collect::<Vec<String>>()
+pub mod error;
+pub mod interval_set;
+pub mod kd_tree;
+pub mod line;
+pub mod point;
+pub mod search;
+pub mod solver;
+pub mod union_find;
+
+pub use error::{Error, ParseError, Result};
+
+pub fn read_input(input: &mut dyn BufRead) -> Result<Vec<String>> {
+  Ok(input.lines().collect::<std::io::Result<Vec<String>>>()?)
 }
 
-pub fn read(file_name: &str) -> BufReader<File> {
-  BufReader::new(File::open(file_name).unwrap())
+pub fn read(file_name: &str) -> Result<BufReader<File>> {
+  Ok(BufReader::new(File::open(file_name)?))
 }
 
-#[derive(Clone)]
+/// Rectangular grid stored row by row in one contiguous vector.
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct CartesianGrid<T> {
-  grid: Vec<Vec<T>>,
+  cells: Vec<T>,
+  width: usize,
+  height: usize,
 }
 
+/// Address of a grid cell. Grid arithmetic stays on these unchecked types;
+/// geometry goes through [`Point`], which they convert into.
 #[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
 pub struct Coords(usize, usize);
 
 #[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
 pub struct ICoords(isize, isize);
 
+/// Orthogonal neighbour offsets: up, right, down, left.
+pub const VON_NEUMANN: [ICoords; 4] =
+  [ICoords(0, -1), ICoords(1, 0), ICoords(0, 1), ICoords(-1, 0)];
+
+/// Orthogonal and diagonal neighbour offsets, clockwise from up.
+pub const MOORE: [ICoords; 8] = [
+  ICoords(0, -1),
+  ICoords(1, -1),
+  ICoords(1, 0),
+  ICoords(1, 1),
+  ICoords(0, 1),
+  ICoords(-1, 1),
+  ICoords(-1, 0),
+  ICoords(-1, -1),
+];
+
+/// What neighbour lookups do with offsets that leave the grid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Edges {
+  /// Drop them.
+  Bounded,
+  /// Wrap them around to the opposite side.
+  Toroidal,
+}
+
 pub trait GridCoords {
   fn in_grid<T>(&self, grid: &CartesianGrid<T>) -> bool;
 }
 
 impl Coords {
   pub fn new(x: usize, y: usize) -> Self {
-    Self { 0: x, 1: y }
+    Self(x, y)
+  }
+
+  fn to_icoords(self) -> ICoords {
+    ICoords::new(self.0 as isize, self.1 as isize)
   }
 
   fn sub_x(&self, v: i32) -> ICoords {
@@ -67,24 +117,33 @@ impl Coords {
   }
 }
 
+impl From<Coords> for Point<usize, 2> {
+  fn from(Coords(x, y): Coords) -> Self {
+    Point::new([x, y])
+  }
+}
+
+impl From<ICoords> for Point<isize, 2> {
+  fn from(ICoords(x, y): ICoords) -> Self {
+    Point::new([x, y])
+  }
+}
+
 impl GridCoords for Coords {
   fn in_grid<T>(&self, grid: &CartesianGrid<T>) -> bool {
-    self.1 < grid.grid.len() && self.0 < grid.grid.get(self.1).unwrap().len()
+    self.0 < grid.width && self.1 < grid.height
   }
 }
 
 impl GridCoords for ICoords {
   fn in_grid<T>(&self, grid: &CartesianGrid<T>) -> bool {
-    self.1 >= 0
-      && self.1 < grid.grid.len() as isize
-      && self.0 >= 0
-      && self.0 < grid.grid.get(self.1 as usize).unwrap().len() as isize
+    self.0 >= 0 && self.1 >= 0 && Coords(self.0 as usize, self.1 as usize).in_grid(grid)
   }
 }
 
 impl ICoords {
   pub fn new(x: isize, y: isize) -> Self {
-    Self { 0: x, 1: y }
+    Self(x, y)
   }
 
   pub fn rem_euclid(&self, x: usize, y: usize) -> Coords {
@@ -94,7 +153,7 @@ impl ICoords {
     )
   }
 
-  fn to_coords(&self) -> Option<Coords> {
+  fn to_coords(self) -> Option<Coords> {
     if self.0 >= 0 && self.1 >= 0 {
       Some(Coords::new(self.0 as usize, self.1 as usize))
     } else {
@@ -129,10 +188,7 @@ impl ops::Add<ICoords> for Coords {
   type Output = ICoords;
 
   fn add(self, rhs: ICoords) -> Self::Output {
-    ICoords {
-      0: self.0 as isize + rhs.0,
-      1: self.1 as isize + rhs.1,
-    }
+    ICoords(self.0 as isize + rhs.0, self.1 as isize + rhs.1)
   }
 }
 
@@ -140,10 +196,7 @@ impl ops::Add<&ICoords> for Coords {
   type Output = ICoords;
 
   fn add(self, rhs: &ICoords) -> Self::Output {
-    ICoords {
-      0: self.0 as isize + rhs.0,
-      1: self.1 as isize + rhs.1,
-    }
+    ICoords(self.0 as isize + rhs.0, self.1 as isize + rhs.1)
   }
 }
 
@@ -151,10 +204,7 @@ impl ops::Add<&ICoords> for &Coords {
   type Output = ICoords;
 
   fn add(self, rhs: &ICoords) -> Self::Output {
-    ICoords {
-      0: self.0 as isize + rhs.0,
-      1: self.1 as isize + rhs.1,
-    }
+    ICoords(self.0 as isize + rhs.0, self.1 as isize + rhs.1)
   }
 }
 
@@ -162,10 +212,7 @@ impl ops::Add<ICoords> for &Coords {
   type Output = ICoords;
 
   fn add(self, rhs: ICoords) -> Self::Output {
-    ICoords {
-      0: self.0 as isize + rhs.0,
-      1: self.1 as isize + rhs.1,
-    }
+    ICoords(self.0 as isize + rhs.0, self.1 as isize + rhs.1)
   }
 }
 
@@ -173,10 +220,7 @@ impl ops::Sub<ICoords> for &Coords {
   type Output = ICoords;
 
   fn sub(self, rhs: ICoords) -> Self::Output {
-    ICoords {
-      0: self.0 as isize - rhs.0,
-      1: self.1 as isize - rhs.1,
-    }
+    ICoords(self.0 as isize - rhs.0, self.1 as isize - rhs.1)
   }
 }
 
@@ -218,8 +262,8 @@ impl ops::Mul<isize> for ICoords {
 
   fn mul(self, n: isize) -> Self::Output {
     Self::Output {
-      0: self.0 * n as isize,
-      1: self.1 * n as isize,
+      0: self.0 * n,
+      1: self.1 * n,
     }
   }
 }
@@ -246,43 +290,146 @@ impl ops::Mul<usize> for ICoords {
   }
 }
 
-impl<T: std::fmt::Display + std::cmp::PartialEq> CartesianGrid<T> {
+impl<T> CartesianGrid<T> {
+  /// Builds a grid from rows that all have `width` cells.
+  fn from_rows(rows: Vec<Vec<T>>, width: usize) -> Self {
+    let height = rows.len();
+    let cells = rows.into_iter().flatten().collect_vec();
+    debug_assert_eq!(cells.len(), width * height);
+
+    CartesianGrid {
+      cells,
+      width,
+      height,
+    }
+  }
+
+  /// Grid of the same shape with `f` applied to every cell.
+  fn map<U>(&self, mut f: impl FnMut(Coords, &T) -> U) -> CartesianGrid<U> {
+    CartesianGrid {
+      cells: self
+        .coords()
+        .into_iter()
+        .zip(&self.cells)
+        .map(|(c, cell)| f(c, cell))
+        .collect(),
+      width: self.width,
+      height: self.height,
+    }
+  }
+
   fn coords(&self) -> Vec<Coords> {
-    (0..self.grid.len())
-      .flat_map(|y| (0..self.grid.get(y).unwrap().len()).map(move |x| Coords::new(x, y)))
+    (0..self.height)
+      .flat_map(|y| (0..self.width).map(move |x| Coords::new(x, y)))
       .collect()
   }
 
   fn coords_at_y(&self, y: usize) -> Vec<Coords> {
-    (0..self.grid.get(y).unwrap().len())
-      .map(|x| Coords::new(x, y))
-      .collect()
+    (0..self.width).map(|x| Coords::new(x, y)).collect()
+  }
+
+  /// Position of `coord` in `cells`. Panics outside the grid, as a column
+  /// past the width would otherwise wrap into the next row.
+  fn index(&self, coord: &Coords) -> usize {
+    assert!(
+      coord.in_grid(self),
+      "{:?} outside the {}x{} grid",
+      coord,
+      self.width,
+      self.height
+    );
+    coord.1 * self.width + coord.0
   }
 
   fn get(&self, coord: &Coords) -> &T {
-    self.grid.get(coord.1).unwrap().get(coord.0).unwrap()
+    &self.cells[self.index(coord)]
   }
 
   fn in_grid(&self, coord: &ICoords) -> bool {
-    coord.1 >= 0
-      && coord.1 < self.grid.len() as isize
-      && coord.0 >= 0
-      && coord.0 < self.grid.get(coord.1 as usize).unwrap().len() as isize
+    coord.in_grid(self)
   }
 
   fn is_boundary(&self, coord: &Coords) -> bool {
-    coord.1 == 0
-      || coord.1 == self.grid.len() - 1
-      || coord.0 == 0
-      || coord.0 == self.grid.get(coord.1 as usize).unwrap().len() - 1
+    coord.1 == 0 || coord.1 == self.height - 1 || coord.0 == 0 || coord.0 == self.width - 1
   }
 
+  fn set(&mut self, coord: &Coords, value: T) {
+    let i = self.index(coord);
+    self.cells[i] = value
+  }
+
+  /// Cells at each of `stencil`'s offsets from `coord`, in stencil order.
+  /// An empty grid has none, and no torus to wrap around.
+  fn neighbours<'a>(
+    &self,
+    coord: Coords,
+    stencil: &'a [ICoords],
+    edges: Edges,
+  ) -> impl Iterator<Item = Coords> + use<'a, T> {
+    let (width, height) = (self.width, self.height);
+    let stencil = if width == 0 || height == 0 {
+      &[]
+    } else {
+      stencil
+    };
+
+    stencil.iter().filter_map(move |offset| {
+      let neighbour = coord + offset;
+      match edges {
+        Edges::Bounded => neighbour
+          .to_coords()
+          .filter(|c| c.0 < width && c.1 < height),
+        Edges::Toroidal => Some(neighbour.rem_euclid(width, height)),
+      }
+    })
+  }
+
+  fn von_neumann_neighbours(&self, coord: Coords) -> impl Iterator<Item = Coords> + use<T> {
+    self.neighbours(coord, &VON_NEUMANN, Edges::Bounded)
+  }
+
+  fn moore_neighbours(&self, coord: Coords) -> impl Iterator<Item = Coords> + use<T> {
+    self.neighbours(coord, &MOORE, Edges::Bounded)
+  }
+
+  fn row(&self, y: usize) -> &[T] {
+    &self.cells[y * self.width..(y + 1) * self.width]
+  }
+
+  fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+    assert!(x < self.width, "column {} outside the grid", x);
+    // A grid without rows has no cells to start the column from.
+    self
+      .cells
+      .get(x..)
+      .unwrap_or(&[])
+      .iter()
+      .step_by(self.width)
+  }
+
+  fn width(&self) -> usize {
+    self.width
+  }
+
+  /// Cells on the segment from `from` to `to`, see [`Line`].
+  fn get_coords_between(&self, from: &Coords, to: &Coords, endpoints: Endpoints) -> Vec<Coords> {
+    Line::new(from.to_icoords(), to.to_icoords(), endpoints)
+      .filter_map(ICoords::to_coords)
+      .collect_vec()
+  }
+
+  fn height(&self) -> usize {
+    self.height
+  }
+}
+
+impl<T: PartialEq> CartesianGrid<T> {
   fn find_one_coords(&self, value: T) -> Option<Coords> {
     self
       .coords()
       .iter()
       .find(|c| *self.get(c) == value)
-      .map(|c| *c)
+      .copied()
   }
 
   fn find_coords(&self, value: T) -> Vec<Coords> {
@@ -290,76 +437,196 @@ impl<T: std::fmt::Display + std::cmp::PartialEq> CartesianGrid<T> {
       .coords()
       .iter()
       .filter(|c| *self.get(c) == value)
-      .map(|c| *c)
+      .copied()
       .collect_vec()
   }
+}
 
-  fn set(&mut self, coord: &Coords, value: T) {
-    self.grid.get_mut(coord.1).unwrap()[coord.0] = value
-  }
-
-  fn get_coords_between(&self, from: &Coords, to: &Coords) -> Vec<Coords> {
-    let dx = from.0 as isize - to.0 as isize;
-    let dy = from.1 as isize - to.1 as isize;
-
-    if dx == 0 {
-      if dy < 0 {
-        (from.1..to.1).map(|y| Coords(from.1, y)).collect_vec()
-      } else {
-        (to.1 + 1..=from.1)
-          .map(|y| Coords(from.1, y))
-          .rev()
-          .collect_vec()
+impl<T: fmt::Display> fmt::Display for CartesianGrid<T> {
+  /// One line per row, so a grid of chars prints as it was parsed.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for y in 0..self.height {
+      if y > 0 {
+        writeln!(f)?;
       }
-    } else if dy == 0 {
-      if dx < 0 {
-        (from.0..to.0).map(|x| Coords(x, from.1)).collect_vec()
-      } else {
-        (to.0 + 1..=from.0)
-          .map(|x| Coords(x, from.1))
-          .rev()
-          .collect_vec()
+      for c in self.row(y) {
+        write!(f, "{}", c)?;
       }
-    } else {
-      // TODO: diagonals
-      vec![]
     }
+    Ok(())
   }
+}
 
-  fn height(&self) -> usize {
-    self.grid.len()
+/// Cell type a grid can be parsed into, one character per cell.
+pub trait FromCell: Sized {
+  /// Description of a valid cell, used in parse errors.
+  const EXPECTED: &'static str;
+
+  fn from_cell(c: char) -> Option<Self>;
+}
+
+impl FromCell for char {
+  const EXPECTED: &'static str = "a character";
+
+  fn from_cell(c: char) -> Option<Self> {
+    Some(c)
   }
+}
 
-  fn print(&self) {
-    for level in self.grid.iter() {
-      for c in level {
-        print!("{} ", c);
+impl<T: FromCell> CartesianGrid<T> {
+  /// Parses one row per line, rejecting rows shorter or longer than the first.
+  pub fn parse(lines: &[String]) -> Result<Self, ParseError> {
+    let rows = Self::parse_rows(lines)?;
+    let width = rows.first().map_or(0, Vec::len);
+
+    for (line, row) in Field::lines(lines).zip(&rows) {
+      if row.len() != width {
+        let found = line.chars().nth(width).map_or(line.end(), |(f, _)| f);
+        return Err(found.error(&format!("a row of {} cells", width)));
       }
-      println!();
     }
+
+    Ok(Self::from_rows(rows, width))
   }
-}
 
-impl CartesianGrid<char> {
-  pub fn from(lines: Vec<String>) -> Self {
-    let grid = lines
-      .iter()
-      .map(|line| line.chars().into_iter().collect())
-      .collect::<Vec<Vec<char>>>();
+  /// Parses one row per line, padding rows shorter than the longest with `fill`.
+  pub fn parse_padded(lines: &[String], fill: T) -> Result<Self, ParseError>
+  where
+    T: Clone,
+  {
+    let mut rows = Self::parse_rows(lines)?;
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
 
-    CartesianGrid { grid }
+    for row in rows.iter_mut() {
+      row.resize(width, fill.clone());
+    }
+
+    Ok(Self::from_rows(rows, width))
   }
-}
 
-trait RemoveFirst<T> {
-  fn remove_first(&mut self) -> Option<T>;
+  fn parse_rows(lines: &[String]) -> Result<Vec<Vec<T>>, ParseError> {
+    Field::lines(lines)
+      .map(|line| {
+        line
+          .chars()
+          .map(|(field, c)| T::from_cell(c).ok_or_else(|| field.error(T::EXPECTED)))
+          .collect()
+      })
+      .collect()
+  }
 }
 
-impl<T> RemoveFirst<T> for Vec<T> {
-  fn remove_first(&mut self) -> Option<T> {
-    if self.is_empty() {
-      return None;
-    }
-    Some(self.remove(0))
+#[cfg(test)]
+mod tests {
+  use itertools::Itertools;
+
+  use crate::{CartesianGrid, Coords, Edges, ICoords, MOORE, ParseError, line::Endpoints};
+
+  fn lines(text: &str) -> Vec<String> {
+    text.lines().map(String::from).collect()
+  }
+
+  #[test]
+  fn rows_and_columns() -> Result<(), ParseError> {
+    let grid = CartesianGrid::<char>::parse(&lines("abc\ndef"))?;
+
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid.row(1), ['d', 'e', 'f']);
+    assert_eq!(grid.column(2).collect::<String>(), "cf");
+    assert_eq!(*grid.get(&Coords::new(1, 1)), 'e');
+    assert_eq!(grid.to_string(), "abc\ndef");
+    Ok(())
+  }
+
+  #[test]
+  fn ragged_rows() -> Result<(), ParseError> {
+    assert_eq!(
+      CartesianGrid::<char>::parse(&lines("abc\nde")).err(),
+      Some(ParseError::new(2, 3, "a row of 3 cells", ""))
+    );
+    assert_eq!(
+      CartesianGrid::<char>::parse(&lines("ab\ncde")).err(),
+      Some(ParseError::new(2, 3, "a row of 2 cells", "e"))
+    );
+
+    let padded = CartesianGrid::parse_padded(&lines("a\nbcd\n"), '.')?;
+    assert_eq!(padded.row(0), ['a', '.', '.']);
+    assert_eq!(padded.column(1).collect::<String>(), ".c");
+    Ok(())
+  }
+
+  #[test]
+  #[should_panic(expected = "outside the 3x2 grid")]
+  fn x_past_the_width() {
+    let grid = CartesianGrid::<char>::parse(&lines("abc\ndef")).unwrap();
+    grid.get(&Coords::new(3, 0));
+  }
+
+  #[test]
+  fn columns_of_an_empty_grid() {
+    let grid = CartesianGrid::<char> {
+      cells: vec![],
+      width: 2,
+      height: 0,
+    };
+    assert_eq!(grid.column(1).count(), 0);
+    assert!(std::panic::catch_unwind(|| grid.column(2).count()).is_err());
+  }
+
+  #[test]
+  fn coords_between() -> Result<(), ParseError> {
+    let grid = CartesianGrid::<char>::parse(&lines("....\n....\n....\n...."))?;
+    let between = |from, to, endpoints| grid.get_coords_between(&from, &to, endpoints);
+
+    assert_eq!(
+      between(Coords::new(1, 0), Coords::new(1, 3), Endpoints::HALF_OPEN),
+      [Coords::new(1, 0), Coords::new(1, 1), Coords::new(1, 2)]
+    );
+    assert_eq!(
+      between(Coords::new(3, 0), Coords::new(0, 3), Endpoints::EXCLUSIVE),
+      [Coords::new(2, 1), Coords::new(1, 2)]
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn neighbours() -> Result<(), ParseError> {
+    let grid = CartesianGrid::<char>::parse(&lines("...\n...\n..."))?;
+
+    assert_eq!(grid.von_neumann_neighbours(Coords::new(1, 1)).count(), 4);
+    assert_eq!(
+      grid.moore_neighbours(Coords::new(0, 0)).collect_vec(),
+      [Coords::new(1, 0), Coords::new(1, 1), Coords::new(0, 1)]
+    );
+    assert_eq!(
+      grid
+        .neighbours(Coords::new(0, 0), &MOORE, Edges::Toroidal)
+        .unique()
+        .count(),
+      8
+    );
+
+    let knight = [ICoords::new(1, 2), ICoords::new(-1, 2), ICoords::new(2, -1)];
+    assert_eq!(
+      grid
+        .neighbours(Coords::new(0, 0), &knight, Edges::Bounded)
+        .collect_vec(),
+      [Coords::new(1, 2)]
+    );
+    assert_eq!(
+      grid
+        .neighbours(Coords::new(0, 0), &knight, Edges::Toroidal)
+        .collect_vec(),
+      [Coords::new(1, 2), Coords::new(2, 2), Coords::new(2, 2)]
+    );
+
+    let empty = CartesianGrid::<char>::parse(&[])?;
+    assert_eq!(
+      empty
+        .neighbours(Coords::new(0, 0), &MOORE, Edges::Toroidal)
+        .count(),
+      0
+    );
+    Ok(())
   }
-}
\ No newline at end of file
+}
@@ -0,0 +1,214 @@
+use crate::ICoords;
+
+/// Which ends of a segment a walk yields.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Endpoints {
+  pub start: bool,
+  pub end: bool,
+}
+
+impl Endpoints {
+  pub const INCLUSIVE: Endpoints = Endpoints {
+    start: true,
+    end: true,
+  };
+  pub const EXCLUSIVE: Endpoints = Endpoints {
+    start: false,
+    end: false,
+  };
+  /// Start included, end excluded, like a `start..end` range.
+  pub const HALF_OPEN: Endpoints = Endpoints {
+    start: true,
+    end: false,
+  };
+}
+
+/// Cells on the segment between two points, walked with Bresenham's
+/// algorithm. Horizontal, vertical and 45° segments come out exact; other
+/// slopes stay within half a cell of the ideal line, one step per cell along
+/// the major axis.
+#[derive(Clone, Debug)]
+pub struct Line {
+  current: ICoords,
+  end: ICoords,
+  dx: isize,
+  dy: isize,
+  step: ICoords,
+  error: isize,
+  endpoints: Endpoints,
+  started: bool,
+  done: bool,
+}
+
+impl Line {
+  pub fn new(from: ICoords, to: ICoords, endpoints: Endpoints) -> Self {
+    let dx = (to.0 - from.0).abs();
+    let dy = -(to.1 - from.1).abs();
+
+    Line {
+      current: from,
+      end: to,
+      dx,
+      dy,
+      step: ICoords((to.0 - from.0).signum(), (to.1 - from.1).signum()),
+      error: dx + dy,
+      endpoints,
+      started: false,
+      done: false,
+    }
+  }
+}
+
+impl Iterator for Line {
+  type Item = ICoords;
+
+  fn next(&mut self) -> Option<ICoords> {
+    while !self.done {
+      let point = self.current;
+      let is_start = !self.started;
+      self.started = true;
+
+      if point == self.end {
+        self.done = true;
+        if !self.endpoints.end {
+          return None;
+        }
+      } else {
+        let twice_error = 2 * self.error;
+        if twice_error >= self.dy {
+          self.error += self.dy;
+          self.current.0 += self.step.0;
+        }
+        if twice_error <= self.dx {
+          self.error += self.dx;
+          self.current.1 += self.step.1;
+        }
+      }
+
+      if !is_start || self.endpoints.start {
+        return Some(point);
+      }
+    }
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use itertools::Itertools;
+  use proptest::prelude::*;
+
+  use crate::{
+    ICoords,
+    line::{Endpoints, Line},
+  };
+
+  fn walk(from: (isize, isize), to: (isize, isize)) -> Vec<ICoords> {
+    Line::new(
+      ICoords::new(from.0, from.1),
+      ICoords::new(to.0, to.1),
+      Endpoints::INCLUSIVE,
+    )
+    .collect()
+  }
+
+  #[test]
+  fn axis_aligned_and_diagonal() {
+    assert_eq!(
+      walk((2, 1), (2, 4)),
+      [(2, 1), (2, 2), (2, 3), (2, 4)].map(|(x, y)| ICoords::new(x, y))
+    );
+    assert_eq!(
+      walk((3, 0), (0, 0)),
+      [(3, 0), (2, 0), (1, 0), (0, 0)].map(|(x, y)| ICoords::new(x, y))
+    );
+    assert_eq!(
+      walk((0, 3), (3, 0)),
+      [(0, 3), (1, 2), (2, 1), (3, 0)].map(|(x, y)| ICoords::new(x, y))
+    );
+  }
+
+  #[test]
+  fn endpoint_options() {
+    let line = |endpoints| {
+      Line::new(ICoords::new(0, 0), ICoords::new(4, 2), endpoints)
+        .map(|c| (c.0, c.1))
+        .collect_vec()
+    };
+
+    assert_eq!(
+      line(Endpoints::INCLUSIVE),
+      [(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]
+    );
+    assert_eq!(line(Endpoints::EXCLUSIVE), [(1, 1), (2, 1), (3, 2)]);
+    assert_eq!(line(Endpoints::HALF_OPEN), [(0, 0), (1, 1), (2, 1), (3, 2)]);
+
+    let point = |endpoints| Line::new(ICoords::new(5, 5), ICoords::new(5, 5), endpoints).count();
+    assert_eq!(point(Endpoints::INCLUSIVE), 1);
+    assert_eq!(point(Endpoints::HALF_OPEN), 0);
+    assert_eq!(point(Endpoints::EXCLUSIVE), 0);
+  }
+
+  proptest! {
+    #[test]
+    fn walks_a_connected_segment(
+      from in (-50isize..50, -50isize..50),
+      to in (-50isize..50, -50isize..50),
+    ) {
+      let points = walk(from, to);
+      let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+      let major = dx.abs().max(dy.abs());
+
+      prop_assert_eq!(points.len() as isize, major + 1);
+      prop_assert_eq!(points.first(), Some(&ICoords::new(from.0, from.1)));
+      prop_assert_eq!(points.last(), Some(&ICoords::new(to.0, to.1)));
+
+      for (a, b) in points.iter().tuple_windows() {
+        let step = (b.0 - a.0, b.1 - a.1);
+        prop_assert!(step.0 == dx.signum() || step.0 == 0);
+        prop_assert!(step.1 == dy.signum() || step.1 == 0);
+        prop_assert!(step != (0, 0));
+      }
+    }
+
+    #[test]
+    fn stays_within_half_a_cell(
+      from in (-50isize..50, -50isize..50),
+      to in (-50isize..50, -50isize..50),
+    ) {
+      let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+      let major = dx.abs().max(dy.abs());
+
+      for p in walk(from, to) {
+        let cross = (p.0 - from.0) * dy - (p.1 - from.1) * dx;
+        prop_assert!(2 * cross.abs() <= major);
+        if dx == 0 || dy == 0 || dx.abs() == dy.abs() {
+          prop_assert_eq!(cross, 0);
+        }
+      }
+    }
+
+    #[test]
+    fn endpoints_trim_the_inclusive_walk(
+      from in (-20isize..20, -20isize..20),
+      to in (-20isize..20, -20isize..20),
+      start: bool,
+      end: bool,
+    ) {
+      let inclusive = walk(from, to);
+      let trimmed = Line::new(
+        ICoords::new(from.0, from.1),
+        ICoords::new(to.0, to.1),
+        Endpoints { start, end },
+      )
+      .collect_vec();
+
+      let expected = if from == to && !(start && end) {
+        vec![]
+      } else {
+        inclusive[usize::from(!start)..inclusive.len() - usize::from(!end)].to_vec()
+      };
+      prop_assert_eq!(trimmed, expected);
+    }
+  }
+}
@@ -1,3 +1,249 @@
-fn main() {
-    println!("Advent of Code 2025");
+use std::{
+  env,
+  fs::File,
+  io::{self, BufRead, BufReader, Cursor},
+  process::ExitCode,
+  time::Instant,
+};
+
+use aoc2025::solver;
+use log::{LevelFilter, Log, Metadata, Record};
+
+const USAGE: &str = "Usage: aoc2025 [DAY|all] [PART] [--input FILE|-] [-v...]
+
+  DAY           day to run (1-10) or `all`, defaults to `all`
+  PART          part to run (1 or 2), defaults to both parts
+  --input FILE  puzzle input, `-` reads stdin, defaults to src/dayNN/my.input
+  -v            log solver diagnostics to stderr, repeat for more detail";
+
+fn main() -> ExitCode {
+  let result = parse_args(env::args().skip(1)).and_then(|args| match args {
+    None => {
+      println!("{}", USAGE);
+      Ok(())
+    }
+    Some(args) => {
+      init_logger(args.verbosity);
+      run(&args)
+    }
+  });
+
+  match result {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(message) => {
+      eprintln!("{}", message);
+      ExitCode::FAILURE
+    }
+  }
+}
+
+#[derive(Debug)]
+struct Args {
+  days: Vec<u8>,
+  parts: Vec<u8>,
+  input: Input,
+  verbosity: u8,
+}
+
+#[derive(Debug)]
+enum Input {
+  Default,
+  Stdin,
+  File(String),
+}
+
+/// The parsed arguments, or None when help was asked for.
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+  let mut positional = vec![];
+  let mut input = Input::Default;
+  let mut verbosity = 0u8;
+  let mut args = args;
+
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "-h" | "--help" => return Ok(None),
+      "-i" | "--input" => {
+        input = match args.next().as_deref() {
+          Some("-") => Input::Stdin,
+          Some(file) => Input::File(file.to_string()),
+          None => return Err(format!("Missing value for {}\n\n{}", arg, USAGE)),
+        }
+      }
+      "--verbose" => verbosity = verbosity.saturating_add(1),
+      _ if arg.len() > 1 && arg.starts_with('-') && arg[1..].bytes().all(|b| b == b'v') => {
+        let count = u8::try_from(arg.len() - 1).unwrap_or(u8::MAX);
+        verbosity = verbosity.saturating_add(count)
+      }
+      _ => positional.push(arg),
+    }
+  }
+
+  let days = match positional.first().map(|d| d.as_str()) {
+    None | Some("all") => solver::days().map(|(day, _)| day).collect(),
+    Some(day) => vec![parse_number(day, "day", solver::days().count() as u8)?],
+  };
+  let parts = match positional.get(1) {
+    None => vec![1, 2],
+    Some(part) => vec![parse_number(part, "part", 2)?],
+  };
+
+  if positional.len() > 2 {
+    return Err(format!(
+      "Unexpected argument {}\n\n{}",
+      positional[2], USAGE
+    ));
+  }
+  if days.len() > 1 && !matches!(input, Input::Default) {
+    return Err("An input can only be given when running a single day".to_string());
+  }
+
+  Ok(Some(Args {
+    days,
+    parts,
+    input,
+    verbosity,
+  }))
+}
+
+fn parse_number(value: &str, name: &str, max: u8) -> Result<u8, String> {
+  value
+    .parse::<u8>()
+    .ok()
+    .filter(|n| (1..=max).contains(n))
+    .ok_or(format!(
+      "Invalid {} {}, expected 1-{}\n\n{}",
+      name, value, max, USAGE
+    ))
+}
+
+fn run(args: &Args) -> Result<(), String> {
+  let stdin = match args.input {
+    Input::Stdin => Some(io::read_to_string(io::stdin()).map_err(|e| e.to_string())?),
+    _ => None,
+  };
+
+  for &day in &args.days {
+    for &part in &args.parts {
+      let (source, mut input): (String, Box<dyn BufRead>) = match &stdin {
+        Some(text) => ("stdin".to_string(), Box::new(Cursor::new(text.clone()))),
+        None => open(&args.input, day)?,
+      };
+      let start = Instant::now();
+
+      match solver::solver(day).and_then(|s| s.solve(part, input.as_mut())) {
+        None => println!("Day {:02} part {}: not solved yet", day, part),
+        Some(Ok(answer)) => println!(
+          "Day {:02} part {}: {} ({:.2?})",
+          day,
+          part,
+          answer,
+          start.elapsed()
+        ),
+        Some(Err(e)) => return Err(format!("Day {:02} part {}: {}: {}", day, part, source, e)),
+      }
+    }
+  }
+
+  Ok(())
+}
+
+/// Writes log records to stderr, prefixed with their level and module.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+  fn enabled(&self, metadata: &Metadata) -> bool {
+    metadata.level() <= log::max_level()
+  }
+
+  fn log(&self, record: &Record) {
+    if self.enabled(record.metadata()) {
+      eprintln!("[{} {}] {}", record.level(), record.target(), record.args());
+    }
+  }
+
+  fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Warnings only by default; each `-v` enables the next level of detail.
+fn init_logger(verbosity: u8) {
+  let level = match verbosity {
+    0 => LevelFilter::Warn,
+    1 => LevelFilter::Info,
+    2 => LevelFilter::Debug,
+    _ => LevelFilter::Trace,
+  };
+
+  if log::set_logger(&LOGGER).is_ok() {
+    log::set_max_level(level);
+  }
+}
+
+fn open(input: &Input, day: u8) -> Result<(String, Box<dyn BufRead>), String> {
+  let file_name = match input {
+    Input::File(file_name) => file_name.clone(),
+    _ => format!("./src/day{:02}/my.input", day),
+  };
+
+  match File::open(&file_name) {
+    Ok(file) => Ok((file_name, Box::new(BufReader::new(file)))),
+    Err(e) => Err(format!("Cannot open input file {}: {}", file_name, e)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{Args, parse_args};
+
+  fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(|a| a.to_string()).collect()
+  }
+
+  fn parsed(line: &str) -> Args {
+    parse_args(args(line).into_iter()).unwrap().unwrap()
+  }
+
+  #[test]
+  fn runs_everything_by_default() {
+    let parsed = parsed("");
+
+    assert_eq!(parsed.days, (1..=10).collect::<Vec<u8>>());
+    assert_eq!(parsed.parts, vec![1, 2]);
+    assert_eq!(parsed.verbosity, 0);
+  }
+
+  #[test]
+  fn selects_day_and_part() {
+    let parsed = parsed("5 2 --input -");
+
+    assert_eq!(parsed.days, vec![5]);
+    assert_eq!(parsed.parts, vec![2]);
+    assert!(matches!(parsed.input, crate::Input::Stdin));
+  }
+
+  #[test]
+  fn rejects_invalid_arguments() {
+    assert!(parse_args(args("11").into_iter()).is_err());
+    assert!(parse_args(args("3 0").into_iter()).is_err());
+    assert!(parse_args(args("all 1 --input my.input").into_iter()).is_err());
+  }
+
+  #[test]
+  fn help_is_not_an_error() {
+    assert!(matches!(parse_args(args("--help").into_iter()), Ok(None)));
+    assert!(matches!(parse_args(args("3 -h").into_iter()), Ok(None)));
+  }
+
+  #[test]
+  fn counts_verbosity() {
+    assert_eq!(parsed("7 -v").verbosity, 1);
+    assert_eq!(parsed("-vv 7 --verbose").verbosity, 3);
+    assert!(parse_args(args("-vx").into_iter()).is_err());
+    assert_eq!(parsed(&format!("-{}", "v".repeat(300))).verbosity, u8::MAX);
+    assert_eq!(
+      parsed(&format!("-{} --verbose", "v".repeat(255))).verbosity,
+      u8::MAX
+    );
+  }
 }
@@ -0,0 +1,177 @@
+use std::{fmt::Debug, hash::Hash, ops::Index};
+
+/// Integer type usable as a point coordinate.
+pub trait Coordinate: Copy + Ord + Hash + Debug + Default {
+  fn checked_add(self, rhs: Self) -> Option<Self>;
+  fn checked_sub(self, rhs: Self) -> Option<Self>;
+  fn checked_mul(self, rhs: Self) -> Option<Self>;
+  fn abs_diff(self, rhs: Self) -> u128;
+}
+
+macro_rules! coordinate {
+  ($($t:ty),*) => {
+    $(
+      impl Coordinate for $t {
+        fn checked_add(self, rhs: Self) -> Option<Self> {
+          <$t>::checked_add(self, rhs)
+        }
+
+        fn checked_sub(self, rhs: Self) -> Option<Self> {
+          <$t>::checked_sub(self, rhs)
+        }
+
+        fn checked_mul(self, rhs: Self) -> Option<Self> {
+          <$t>::checked_mul(self, rhs)
+        }
+
+        fn abs_diff(self, rhs: Self) -> u128 {
+          <$t>::abs_diff(self, rhs) as u128
+        }
+      }
+    )*
+  };
+}
+
+coordinate!(i32, i64, isize, u32, u64, usize);
+
+/// Point in `N` dimensions. Arithmetic is checked and distances are exact,
+/// so overflow surfaces as `None` instead of wrapping.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct Point<T, const N: usize>(pub [T; N]);
+
+impl<T: Coordinate, const N: usize> Point<T, N> {
+  pub fn new(coords: [T; N]) -> Self {
+    Point(coords)
+  }
+
+  pub fn origin() -> Self {
+    Point([T::default(); N])
+  }
+
+  pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+    self.zip_with(rhs, T::checked_add)
+  }
+
+  pub fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+    self.zip_with(rhs, T::checked_sub)
+  }
+
+  pub fn checked_scale(&self, factor: T) -> Option<Self> {
+    self.zip_with(&Point([factor; N]), T::checked_mul)
+  }
+
+  /// Absolute difference along each axis.
+  pub fn axis_distances(&self, other: &Self) -> [u128; N] {
+    std::array::from_fn(|a| self.0[a].abs_diff(other.0[a]))
+  }
+
+  pub fn manhattan(&self, other: &Self) -> u128 {
+    self.axis_distances(other).iter().sum()
+  }
+
+  pub fn chebyshev(&self, other: &Self) -> u128 {
+    self.axis_distances(other).into_iter().max().unwrap_or(0)
+  }
+
+  /// None when the sum of squares does not fit in `u128`, which takes full
+  /// range 64-bit coordinates in two or more dimensions.
+  pub fn squared_euclidean(&self, other: &Self) -> Option<u128> {
+    self
+      .axis_distances(other)
+      .iter()
+      .try_fold(0u128, |sum, d| sum.checked_add(d.checked_mul(*d)?))
+  }
+
+  fn zip_with(&self, rhs: &Self, f: impl Fn(T, T) -> Option<T>) -> Option<Self> {
+    let mut coords = self.0;
+    for (c, r) in coords.iter_mut().zip(rhs.0) {
+      *c = f(*c, r)?;
+    }
+    Some(Point(coords))
+  }
+}
+
+impl<T: Copy> Point<T, 2> {
+  pub fn x(&self) -> T {
+    self.0[0]
+  }
+
+  pub fn y(&self) -> T {
+    self.0[1]
+  }
+}
+
+impl<T: Copy> Point<T, 3> {
+  pub fn x(&self) -> T {
+    self.0[0]
+  }
+
+  pub fn y(&self) -> T {
+    self.0[1]
+  }
+
+  pub fn z(&self) -> T {
+    self.0[2]
+  }
+}
+
+impl<T, const N: usize> Index<usize> for Point<T, N> {
+  type Output = T;
+
+  fn index(&self, axis: usize) -> &T {
+    &self.0[axis]
+  }
+}
+
+impl<T, const N: usize> From<[T; N]> for Point<T, N> {
+  fn from(coords: [T; N]) -> Self {
+    Point(coords)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{Coords, ICoords, point::Point};
+
+  #[test]
+  fn checked_arithmetic() {
+    let a = Point::new([3i32, -2, 7]);
+    let b = Point::new([1, 5, -7]);
+
+    assert_eq!(a.checked_add(&b), Some(Point::new([4, 3, 0])));
+    assert_eq!(a.checked_sub(&b), Some(Point::new([2, -7, 14])));
+    assert_eq!(a.checked_scale(-2), Some(Point::new([-6, 4, -14])));
+    assert_eq!(
+      Point::new([i32::MAX, 0]).checked_add(&Point::new([1, 0])),
+      None
+    );
+    assert_eq!(
+      Point::new([0usize, 1]).checked_sub(&Point::new([1, 0])),
+      None
+    );
+  }
+
+  #[test]
+  fn metrics() {
+    let a = Point::new([1i64, -2, 3]);
+    let b = Point::new([4, 2, 3]);
+
+    assert_eq!(a.manhattan(&b), 7);
+    assert_eq!(a.chebyshev(&b), 4);
+    assert_eq!(a.squared_euclidean(&b), Some(25));
+    assert_eq!(
+      Point::new([i64::MIN]).squared_euclidean(&Point::new([i64::MAX])),
+      Some((u64::MAX as u128).pow(2))
+    );
+    assert_eq!(
+      Point::new([0u64, 0]).squared_euclidean(&Point::new([u64::MAX, u64::MAX])),
+      None
+    );
+  }
+
+  #[test]
+  fn converts_grid_coords() {
+    assert_eq!(Point::from(Coords::new(2, 5)), Point::new([2usize, 5]));
+    assert_eq!(Point::from(ICoords::new(-2, 5)), Point::new([-2isize, 5]));
+  }
+}
@@ -0,0 +1,263 @@
+use std::{
+  cmp::Reverse,
+  collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+  hash::Hash,
+  ops::Add,
+};
+
+use crate::bitset::BitSet;
+
+/// States from the start to the goal, and the cost of getting there.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Path<S, C> {
+  pub states: Vec<S>,
+  pub cost: C,
+}
+
+impl<S, C> Path<S, C> {
+  /// Number of moves taken.
+  pub fn steps(&self) -> usize {
+    self.states.len() - 1
+  }
+}
+
+/// How a search remembers the states it has already settled.
+pub trait Visited<S> {
+  /// Marks `state` as visited, returning false when it already was.
+  fn visit(&mut self, state: &S) -> bool;
+}
+
+impl<S: Hash + Eq + Clone> Visited<S> for HashSet<S> {
+  fn visit(&mut self, state: &S) -> bool {
+    self.insert(state.clone())
+  }
+}
+
+/// Dense visited set for states numbered from zero.
+impl Visited<usize> for BitSet {
+  fn visit(&mut self, state: &usize) -> bool {
+    let new = !self.contains(*state);
+    self.insert(*state);
+    new
+  }
+}
+
+/// Remembers nothing, for state spaces that are trees.
+pub struct Unvisited;
+
+impl<S> Visited<S> for Unvisited {
+  fn visit(&mut self, _: &S) -> bool {
+    true
+  }
+}
+
+/// Explored states, each pointing back at the state it was reached from.
+struct Arena<S> {
+  nodes: Vec<(S, Option<usize>)>,
+}
+
+impl<S: Clone> Arena<S> {
+  fn push(&mut self, state: S, parent: Option<usize>) -> usize {
+    self.nodes.push((state, parent));
+    self.nodes.len() - 1
+  }
+
+  fn path<C>(&self, mut node: usize, cost: C) -> Path<S, C> {
+    let mut states = vec![self.nodes[node].0.clone()];
+    while let Some(parent) = self.nodes[node].1 {
+      states.push(self.nodes[parent].0.clone());
+      node = parent;
+    }
+    states.reverse();
+    Path { states, cost }
+  }
+}
+
+/// Breadth-first search for the goal fewest moves away, stopping as soon as
+/// one is dequeued.
+pub fn bfs<S, I>(
+  start: S,
+  mut successors: impl FnMut(&S) -> I,
+  mut is_goal: impl FnMut(&S) -> bool,
+  visited: &mut impl Visited<S>,
+) -> Option<Path<S, usize>>
+where
+  S: Clone,
+  I: IntoIterator<Item = S>,
+{
+  let mut arena = Arena { nodes: vec![] };
+  let mut queue = VecDeque::new();
+
+  visited.visit(&start);
+  queue.push_back((arena.push(start, None), 0));
+
+  while let Some((node, depth)) = queue.pop_front() {
+    if is_goal(&arena.nodes[node].0) {
+      return Some(arena.path(node, depth));
+    }
+
+    for next in successors(&arena.nodes[node].0) {
+      if visited.visit(&next) {
+        queue.push_back((arena.push(next, Some(node)), depth + 1));
+      }
+    }
+  }
+  None
+}
+
+/// Cheapest path to a goal over non-negative move costs.
+pub fn dijkstra<S, C, I>(
+  start: S,
+  successors: impl FnMut(&S) -> I,
+  is_goal: impl FnMut(&S) -> bool,
+  visited: &mut impl Visited<S>,
+) -> Option<Path<S, C>>
+where
+  S: Clone + Hash + Eq,
+  C: Copy + Ord + Default + Add<Output = C>,
+  I: IntoIterator<Item = (S, C)>,
+{
+  astar(start, successors, |_| C::default(), is_goal, visited)
+}
+
+/// Cheapest path to a goal, expanding states in order of cost so far plus
+/// `heuristic`. The heuristic must never overestimate the remaining cost and
+/// must be consistent for the first goal reached to be the cheapest.
+pub fn astar<S, C, I>(
+  start: S,
+  mut successors: impl FnMut(&S) -> I,
+  mut heuristic: impl FnMut(&S) -> C,
+  mut is_goal: impl FnMut(&S) -> bool,
+  visited: &mut impl Visited<S>,
+) -> Option<Path<S, C>>
+where
+  S: Clone + Hash + Eq,
+  C: Copy + Ord + Default + Add<Output = C>,
+  I: IntoIterator<Item = (S, C)>,
+{
+  let mut arena = Arena { nodes: vec![] };
+  let mut queue = BinaryHeap::new();
+  // Cheapest known cost of every queued state, so that a state is only queued
+  // again when a cheaper way to it turns up.
+  let mut best = HashMap::new();
+
+  best.insert(start.clone(), C::default());
+  let estimate = heuristic(&start);
+  queue.push(Reverse((estimate, arena.push(start, None), C::default())));
+
+  // Ties are broken by arena index, so equally promising states are expanded
+  // in the order they were found.
+  while let Some(Reverse((_, node, cost))) = queue.pop() {
+    let state = &arena.nodes[node].0;
+    if !visited.visit(state) {
+      continue;
+    }
+    if is_goal(state) {
+      return Some(arena.path(node, cost));
+    }
+
+    for (next, step) in successors(state) {
+      let cost = cost + step;
+      if best.get(&next).is_some_and(|&known| known <= cost) {
+        continue;
+      }
+      best.insert(next.clone(), cost);
+      let estimate = cost + heuristic(&next);
+      queue.push(Reverse((estimate, arena.push(next, Some(node)), cost)));
+    }
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashSet;
+
+  use crate::{
+    CartesianGrid, Coords, ParseError,
+    bitset::BitSet,
+    point::Point,
+    search::{Unvisited, astar, bfs, dijkstra},
+  };
+
+  fn maze() -> Result<CartesianGrid<char>, ParseError> {
+    let lines = ["S.#....", ".##.##.", "....#..", ".##...#", "...#..E"];
+    CartesianGrid::parse(&lines.map(String::from))
+  }
+
+  #[test]
+  fn shortest_path_through_a_maze() -> Result<(), ParseError> {
+    let maze = maze()?;
+    let open = |c: &Coords| *maze.get(c) != '#';
+    let end = Coords::new(6, 4);
+
+    let path = bfs(
+      Coords::new(0, 0),
+      |&c| maze.von_neumann_neighbours(c).filter(open),
+      |&c| c == end,
+      &mut HashSet::new(),
+    )
+    .unwrap();
+    assert_eq!(path.steps(), 10);
+    assert_eq!(path.states.first(), Some(&Coords::new(0, 0)));
+    assert_eq!(path.states.last(), Some(&end));
+    for pair in path.states.windows(2) {
+      assert_eq!(Point::from(pair[0]).manhattan(&Point::from(pair[1])), 1);
+    }
+
+    let weighted = |&c: &Coords| {
+      maze
+        .von_neumann_neighbours(c)
+        .filter(open)
+        .map(|n| (n, 1 + n.1))
+        .collect::<Vec<_>>()
+    };
+    let cheapest = dijkstra(
+      Coords::new(0, 0),
+      weighted,
+      |&c| c == end,
+      &mut HashSet::new(),
+    )
+    .unwrap();
+    let guided = astar(
+      Coords::new(0, 0),
+      weighted,
+      |&c| Point::from(c).manhattan(&Point::from(end)) as usize,
+      |&c| c == end,
+      &mut HashSet::new(),
+    )
+    .unwrap();
+    assert_eq!(guided.cost, cheapest.cost);
+    assert_eq!(cheapest.cost, 36);
+
+    assert_eq!(
+      bfs(
+        Coords::new(0, 0),
+        |&c| maze.von_neumann_neighbours(c).filter(open),
+        |&c| c == Coords::new(2, 0),
+        &mut HashSet::new(),
+      ),
+      None
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn visited_strategies() {
+    // Small numbers are dense states, so a bit set can track them.
+    let moves = |&n: &usize| [n + 1, n * 2].into_iter().filter(|&m| m < 100);
+    let path = bfs(1, moves, |&n| n == 27, &mut BitSet::new()).unwrap();
+    assert_eq!(path.steps(), 7);
+    assert!(
+      path
+        .states
+        .windows(2)
+        .all(|w| w[1] == w[0] + 1 || w[1] == w[0] * 2)
+    );
+
+    // Binary strings form a tree, so nothing needs remembering.
+    let children = |s: &String| [format!("{}0", s), format!("{}1", s)];
+    let path = bfs(String::new(), children, |s| s == "101", &mut Unvisited).unwrap();
+    assert_eq!(path.states, ["", "1", "10", "101"]);
+  }
+}
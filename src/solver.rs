@@ -0,0 +1,140 @@
+use std::{fmt, io::BufRead};
+
+use num_bigint::BigUint;
+
+use crate::{Result, day01, day02, day03, day04, day05, day06, day07, day08, day09, day10};
+
+pub trait Solver: Sync {
+  fn part1(&self, input: &mut dyn BufRead) -> Result<Answer>;
+  fn part2(&self, input: &mut dyn BufRead) -> Result<Answer>;
+
+  fn solve(&self, part: u8, input: &mut dyn BufRead) -> Option<Result<Answer>> {
+    match part {
+      1 => Some(self.part1(input)),
+      2 => Some(self.part2(input)),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Answer {
+  Number(u128),
+  Text(String),
+}
+
+impl fmt::Display for Answer {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Answer::Number(n) => write!(f, "{}", n),
+      Answer::Text(s) => write!(f, "{}", s),
+    }
+  }
+}
+
+macro_rules! answer_from_number {
+  ($($t:ty),*) => {
+    $(
+      impl From<$t> for Answer {
+        fn from(n: $t) -> Self {
+          Answer::Number(n as u128)
+        }
+      }
+    )*
+  };
+}
+
+answer_from_number!(u32, u64, usize, u128);
+
+/// A number too big for `u128` is given as its decimal digits.
+impl From<BigUint> for Answer {
+  fn from(n: BigUint) -> Self {
+    match u128::try_from(&n) {
+      Ok(n) => Answer::Number(n),
+      Err(_) => Answer::Text(n.to_string()),
+    }
+  }
+}
+
+impl From<String> for Answer {
+  fn from(s: String) -> Self {
+    Answer::Text(s)
+  }
+}
+
+static SOLVERS: [&dyn Solver; 10] = [
+  &day01::Day01,
+  &day02::Day02,
+  &day03::Day03,
+  &day04::Day04,
+  &day05::Day05,
+  &day06::Day06,
+  &day07::Day07,
+  &day08::Day08 { connections: 1000 },
+  &day09::Day09,
+  &day10::Day10,
+];
+
+pub fn days() -> impl Iterator<Item = (u8, &'static dyn Solver)> {
+  SOLVERS.iter().enumerate().map(|(i, s)| (i as u8 + 1, *s))
+}
+
+pub fn solver(day: u8) -> Option<&'static dyn Solver> {
+  SOLVERS.get((day as usize).checked_sub(1)?).copied()
+}
+
+#[cfg(test)]
+mod tests {
+  use num_bigint::BigUint;
+
+  use crate::{
+    Result, read,
+    solver::{Answer, days, solver},
+  };
+
+  #[test]
+  fn registry_covers_every_day() {
+    assert_eq!(
+      days().map(|(d, _)| d).collect::<Vec<_>>(),
+      (1..=10).collect::<Vec<_>>()
+    );
+    assert!(solver(0).is_none());
+    assert!(solver(11).is_none());
+  }
+
+  #[test]
+  fn solves_samples_through_registry() -> Result<()> {
+    let expected = [
+      (1, 3),
+      (2, 1227775554),
+      (3, 357),
+      (5, 3),
+      (6, 4277556),
+      (7, 21),
+    ];
+
+    for (day, answer) in expected {
+      let input = format!("./src/day{:02}/sample.input", day);
+      assert_eq!(
+        solver(day)
+          .unwrap()
+          .solve(1, &mut read(&input)?)
+          .transpose()?,
+        Some(Answer::Number(answer))
+      );
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn big_numbers_fall_back_to_text() {
+    assert_eq!(
+      Answer::from(BigUint::from(u128::MAX)),
+      Answer::Number(u128::MAX)
+    );
+    assert_eq!(
+      Answer::from(BigUint::from(u128::MAX) + 1u32),
+      Answer::Text("340282366920938463463374607431768211456".to_string())
+    );
+  }
+}
@@ -0,0 +1,105 @@
+/// Disjoint sets over `0..n` with path compression and union by size.
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+  parent: Vec<usize>,
+  size: Vec<usize>,
+  components: usize,
+}
+
+impl UnionFind {
+  pub fn new(n: usize) -> Self {
+    UnionFind {
+      parent: (0..n).collect(),
+      size: vec![1; n],
+      components: n,
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.parent.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.parent.is_empty()
+  }
+
+  pub fn find(&mut self, x: usize) -> usize {
+    let mut root = x;
+    while self.parent[root] != root {
+      root = self.parent[root];
+    }
+
+    let mut current = x;
+    while self.parent[current] != root {
+      let next = self.parent[current];
+      self.parent[current] = root;
+      current = next;
+    }
+
+    root
+  }
+
+  /// Merges the sets holding `a` and `b`, returning false when they were
+  /// already the same set.
+  pub fn union(&mut self, a: usize, b: usize) -> bool {
+    let (mut a, mut b) = (self.find(a), self.find(b));
+    if a == b {
+      return false;
+    }
+    if self.size[a] < self.size[b] {
+      (a, b) = (b, a);
+    }
+
+    self.parent[b] = a;
+    self.size[a] += self.size[b];
+    self.components -= 1;
+    true
+  }
+
+  pub fn connected(&mut self, a: usize, b: usize) -> bool {
+    self.find(a) == self.find(b)
+  }
+
+  /// Size of the set holding `x`.
+  pub fn size_of(&mut self, x: usize) -> usize {
+    let root = self.find(x);
+    self.size[root]
+  }
+
+  pub fn components(&self) -> usize {
+    self.components
+  }
+
+  /// Size of every set, one entry per set.
+  pub fn component_sizes(&self) -> Vec<usize> {
+    (0..self.len())
+      .filter(|&x| self.parent[x] == x)
+      .map(|root| self.size[root])
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::union_find::UnionFind;
+
+  #[test]
+  fn merges_sets() {
+    let mut sets = UnionFind::new(6);
+
+    assert!(sets.union(0, 1));
+    assert!(sets.union(2, 3));
+    assert!(sets.union(1, 3));
+    assert!(!sets.union(0, 2));
+
+    assert!(sets.connected(0, 3));
+    assert!(!sets.connected(0, 4));
+    assert_eq!(sets.size_of(2), 4);
+    assert_eq!(sets.components(), 3);
+    assert_eq!(sets.component_sizes().iter().sum::<usize>(), 6);
+
+    let mut sizes = sets.component_sizes();
+    sizes.sort();
+    assert_eq!(sizes, vec![1, 1, 4]);
+  }
+}